@@ -41,4 +41,8 @@ for (( i = 1; i <= 24; i++ )); do
   aoc "$i" 2
 done
 
+echo "Recording machine readable results"
+cargo run --release -- --format json > times.json
+cargo run --release -- --format csv > times.csv
+
 exit 0
\ No newline at end of file
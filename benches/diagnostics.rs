@@ -0,0 +1,45 @@
+//! Benchmarks of day 3 on generated diagnostic reports of a million lines
+//!
+//! ```bash
+//! cargo bench --bench diagnostics
+//! ```
+
+use adventofcode_rust_2021::runner::solutions;
+use criterion::{criterion_group, criterion_main, Criterion};
+use std::hint::black_box;
+
+const LINES: usize = 1_000_000;
+
+/// report of pseudo random numbers of given width, the same on every run
+fn report(width: usize) -> String {
+    let mut state = 0x2545_f491_4f6c_dd1du64;
+    let mut report = String::with_capacity(LINES * (width + 1));
+    for _ in 0..LINES {
+        for _ in 0..width {
+            // xorshift
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            report.push(if state & 1 == 1 { '1' } else { '0' });
+        }
+        report.push('\n');
+    }
+    report
+}
+
+/// both parts including parsing, for the width of the real input and for wide numbers
+fn reports(c: &mut Criterion) {
+    let mut group = c.benchmark_group("diagnostics");
+    group.sample_size(10);
+    for width in [12, 64] {
+        let input = report(width);
+        for solution in solutions().into_iter().filter(|s| s.day == 3) {
+            let name = format!("{} bits part{}", width, solution.part);
+            group.bench_function(name, |b| b.iter(|| solution.run(black_box(&input))));
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, reports);
+criterion_main!(benches);
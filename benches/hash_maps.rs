@@ -0,0 +1,306 @@
+//! Benchmarks of the days built around small maps, and of the map types their hot paths can use
+//!
+//! ```bash
+//! cargo bench --bench hash_maps
+//! ```
+
+use adventofcode_rust_2021::answer::Answer;
+use adventofcode_rust_2021::runner::{load_input, solutions};
+use criterion::{criterion_group, criterion_main, Criterion};
+use rustc_hash::FxHashMap;
+use std::collections::HashMap;
+use std::hint::black_box;
+
+/// both parts of the days 8, 12 and 14 on the real input, including parsing, next to the
+/// std `HashMap` implementations of their hot paths they replaced
+fn days(c: &mut Criterion) {
+    let mut group = c.benchmark_group("days");
+    for solution in solutions()
+        .into_iter()
+        .filter(|s| [8, 12, 14].contains(&s.day))
+    {
+        let input = load_input(solution.day).expect("failed to read input");
+        let name = format!("day{} part{}", solution.day, solution.part);
+        group.bench_function(name, |b| b.iter(|| solution.run(black_box(&input))));
+        let baseline = match (solution.day, solution.part) {
+            (8, 2) => baseline::day8_part2,
+            (12, 1) => baseline::day12_part1,
+            (14, 2) => baseline::day14_part2,
+            _ => continue,
+        };
+        let answer = solution.run(&input).expect("failed to solve").answer;
+        assert_eq!(Answer::from(baseline(&input)), answer, "baseline differs");
+        let name = format!("day{} part{} (HashMap)", solution.day, solution.part);
+        group.bench_function(name, |b| b.iter(|| baseline(black_box(&input))));
+    }
+    group.finish();
+}
+
+/// The hot paths of days 8, 12 and 14 as they were before moving to dense tables and Fx
+/// hashing, solving straight from the puzzle input.
+mod baseline {
+    use petgraph::graph::NodeIndex;
+    use petgraph::visit::EdgeRef;
+    use petgraph::Graph;
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+    use std::rc::Rc;
+
+    /// sum of the output values, digits being looked up by their sorted pattern
+    pub fn day8_part2(input: &str) -> usize {
+        let sort = |s: &str| {
+            let mut chars: Vec<char> = s.chars().collect();
+            chars.sort_unstable();
+            String::from_iter(chars)
+        };
+        let contains =
+            |haystack: &str, needles: &str| needles.chars().all(|c| haystack.contains(c));
+        let mut sum = 0;
+        for line in input.lines() {
+            let (patterns, outputs) = line.split_once(" | ").expect("invalid entry");
+            let patterns: Vec<String> = patterns.split(' ').map(sort).collect();
+            let find = |predicate: &dyn Fn(&str) -> bool| {
+                patterns
+                    .iter()
+                    .find(|s| predicate(s))
+                    .expect("failed to find digit")
+                    .as_str()
+            };
+            let one = find(&|s| s.len() == 2);
+            let three = find(&|s| s.len() == 5 && contains(s, one));
+            let nine = find(&|s| s.len() == 6 && contains(s, three));
+            let zero = find(&|s| s.len() == 6 && s != nine && contains(s, one));
+            let six = find(&|s| s.len() == 6 && s != nine && s != zero);
+            let five = find(&|s| s.len() == 5 && s != three && contains(six, s));
+            let two = find(&|s| s.len() == 5 && s != three && s != five);
+            let mut map: HashMap<&str, usize> = HashMap::new();
+            map.insert(zero, 0);
+            map.insert(one, 1);
+            map.insert(two, 2);
+            map.insert(three, 3);
+            map.insert(find(&|s| s.len() == 4), 4);
+            map.insert(five, 5);
+            map.insert(six, 6);
+            map.insert(find(&|s| s.len() == 3), 7);
+            map.insert(find(&|s| s.len() == 7), 8);
+            map.insert(nine, 9);
+            sum += outputs
+                .split(' ')
+                .fold(0, |value, digit| value * 10 + map[sort(digit).as_str()]);
+        }
+        sum
+    }
+
+    /// number of paths visiting small caves at most once, on a petgraph graph which keeps every
+    /// path taken so far and copies the visit counts on every step
+    pub fn day12_part1(input: &str) -> usize {
+        build_paths(&parse_caves(input), 1).len()
+    }
+
+    #[allow(dead_code)]
+    #[derive(Clone, Debug)]
+    enum CaveNode {
+        Start,
+        End,
+        SmallCave(String),
+        BigCave(String),
+    }
+
+    impl CaveNode {
+        fn parse(name: &str) -> CaveNode {
+            match name {
+                "start" => CaveNode::Start,
+                "end" => CaveNode::End,
+                name => match name.chars().next().unwrap().is_lowercase() {
+                    true => CaveNode::SmallCave(name.to_string()),
+                    false => CaveNode::BigCave(name.to_string()),
+                },
+            }
+        }
+    }
+
+    fn parse_caves(input: &str) -> Graph<CaveNode, ()> {
+        let mut graph = Graph::new();
+        let mut node_by_name: HashMap<String, NodeIndex> = HashMap::new();
+        for line in input.lines() {
+            let parts: Vec<&str> = line.split('-').collect();
+            let from_name = parts[0];
+            let to_name = parts[1];
+            if !node_by_name.contains_key(from_name) {
+                let idx = graph.add_node(CaveNode::parse(from_name));
+                node_by_name.insert(from_name.to_string(), idx);
+            }
+            if !node_by_name.contains_key(to_name) {
+                let idx = graph.add_node(CaveNode::parse(to_name));
+                node_by_name.insert(to_name.to_string(), idx);
+            }
+            let from_idx = node_by_name[from_name];
+            let to_idx = node_by_name[to_name];
+            graph.add_edge(from_idx, to_idx, ());
+            graph.add_edge(to_idx, from_idx, ());
+        }
+        graph
+    }
+
+    #[allow(clippy::collapsible_match)]
+    fn decend(
+        graph: &Graph<CaveNode, ()>,
+        visited: Rc<RefCell<Vec<Vec<NodeIndex>>>>,
+        mut small_caves: HashMap<NodeIndex, u8>,
+        max_small_caves: u8,
+        path: Vec<NodeIndex>,
+    ) -> Vec<Vec<NodeIndex>> {
+        let mut paths = Vec::new();
+        let last_idx = path[path.len() - 1];
+        let last_node = graph.node_weight(last_idx).unwrap();
+        if let CaveNode::SmallCave(_) = last_node {
+            small_caves.entry(last_idx).or_insert(0);
+            *small_caves.get_mut(&last_idx).unwrap() += 1;
+        }
+        for edge in graph.edges(last_idx) {
+            let target_idx = edge.target();
+            let target_node = graph.node_weight(target_idx).unwrap();
+
+            match target_node {
+                CaveNode::Start => {}
+                CaveNode::End => {
+                    let mut new_path = path.clone();
+                    new_path.push(target_idx);
+                    paths.push(new_path);
+                }
+                CaveNode::SmallCave(_) => {
+                    if small_caves.contains_key(&target_idx) {
+                        let cnt = small_caves[&target_idx];
+                        if cnt >= max_small_caves {
+                            continue;
+                        } else if max_small_caves > 1 {
+                            let mut found_bad = false;
+                            for key in small_caves.keys() {
+                                if *key != target_idx && small_caves[key] >= max_small_caves {
+                                    found_bad = true;
+                                }
+                            }
+                            if found_bad {
+                                continue;
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            };
+            match target_node {
+                CaveNode::SmallCave(_) | CaveNode::BigCave(_) => {
+                    let mut new_path = path.clone();
+                    new_path.push(target_idx);
+                    if !visited.borrow().contains(&new_path) {
+                        visited.borrow_mut().push(new_path.clone());
+                        paths.append(&mut decend(
+                            graph,
+                            visited.clone(),
+                            small_caves.clone(),
+                            max_small_caves,
+                            new_path,
+                        ));
+                    }
+                }
+                _ => {}
+            };
+        }
+        paths
+    }
+
+    fn build_paths(graph: &Graph<CaveNode, ()>, max_small_caves: u8) -> Vec<Vec<NodeIndex>> {
+        let mut paths = Vec::new();
+        let history = Rc::new(RefCell::new(Vec::new()));
+
+        for node_idx in graph.node_indices() {
+            if let CaveNode::Start = graph.node_weight(node_idx).unwrap() {
+                for edge in graph.edges(node_idx) {
+                    let target_idx = edge.target();
+                    let small_caves = HashMap::new();
+                    paths.append(&mut decend(
+                        graph,
+                        history.clone(),
+                        small_caves,
+                        max_small_caves,
+                        vec![node_idx, target_idx],
+                    ));
+                }
+                break;
+            }
+        }
+        paths
+    }
+
+    /// most minus least common element after 40 steps, counting letter pairs in maps
+    pub fn day14_part2(input: &str) -> usize {
+        let (start, rules) = input.split_once("\n\n").expect("missing rules");
+        let rules: HashMap<(char, char), char> = rules
+            .lines()
+            .map(|line| {
+                let mut chars = line.chars();
+                let (a, b) = (chars.next().unwrap(), chars.next().unwrap());
+                ((a, b), chars.last().unwrap())
+            })
+            .collect();
+        let chars: Vec<char> = start.chars().collect();
+        let mut pairs: HashMap<(char, char), usize> = HashMap::new();
+        for w in chars.windows(2) {
+            *pairs.entry((w[0], w[1])).or_default() += 1;
+        }
+        let mut frequencies: HashMap<char, usize> = HashMap::new();
+        for c in chars {
+            *frequencies.entry(c).or_default() += 1;
+        }
+        for _ in 0..40 {
+            let mut next = pairs.clone();
+            for (&(a, b), &cnt) in &pairs {
+                if let (Some(&c), true) = (rules.get(&(a, b)), cnt > 0) {
+                    *next.get_mut(&(a, b)).unwrap() -= cnt;
+                    *next.entry((a, c)).or_default() += cnt;
+                    *next.entry((c, b)).or_default() += cnt;
+                    *frequencies.entry(c).or_default() += cnt;
+                }
+            }
+            pairs = next;
+        }
+        frequencies.values().max().unwrap() - frequencies.values().min().unwrap()
+    }
+}
+
+/// counting the letter pairs of a polymer like day 14 does in every step
+fn pair_counts(c: &mut Criterion) {
+    let polymer: Vec<u8> = (0..100_000u32).map(|i| b'A' + (i * 7 % 13) as u8).collect();
+    let mut group = c.benchmark_group("pair counts");
+    group.bench_function("HashMap", |b| {
+        b.iter(|| {
+            let mut counts: HashMap<(u8, u8), usize> = HashMap::new();
+            for w in black_box(&polymer).windows(2) {
+                *counts.entry((w[0], w[1])).or_default() += 1;
+            }
+            counts.len()
+        })
+    });
+    group.bench_function("FxHashMap", |b| {
+        b.iter(|| {
+            let mut counts: FxHashMap<(u8, u8), usize> = FxHashMap::default();
+            for w in black_box(&polymer).windows(2) {
+                *counts.entry((w[0], w[1])).or_default() += 1;
+            }
+            counts.len()
+        })
+    });
+    group.bench_function("26x26 table", |b| {
+        b.iter(|| {
+            let mut counts = [0usize; 26 * 26];
+            for w in black_box(&polymer).windows(2) {
+                counts[(w[0] - b'A') as usize * 26 + (w[1] - b'A') as usize] += 1;
+            }
+            counts.iter().filter(|&&n| n > 0).count()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, days, pair_counts);
+criterion_main!(benches);
@@ -0,0 +1,11 @@
+#![no_main]
+use adventofcode_rust_2021::runner::exercise;
+use libfuzzer_sys::fuzz_target;
+use std::time::Duration;
+
+// day 10 has no generator, this runs `check_line` on every line before solving
+fuzz_target!(|data: &[u8]| {
+    if let Ok(input) = std::str::from_utf8(data) {
+        let _ = exercise(10, input, Duration::from_millis(100));
+    }
+});
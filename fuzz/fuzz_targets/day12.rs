@@ -0,0 +1,10 @@
+#![no_main]
+use adventofcode_rust_2021::runner::exercise;
+use libfuzzer_sys::fuzz_target;
+use std::time::Duration;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(input) = std::str::from_utf8(data) {
+        let _ = exercise(12, input, Duration::from_millis(100));
+    }
+});
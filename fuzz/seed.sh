@@ -0,0 +1,12 @@
+#!/usr/bin/env bash
+# fills the fuzz corpus with the puzzle examples and the real puzzle inputs
+
+cd "$(dirname "$0")" || exit 1
+
+for (( i = 1; i <= 15; i++ )); do
+  mkdir -p "corpus/day$i"
+  cp seeds/day"$i"/* "corpus/day$i/"
+  if test -f "../input/2021/day$i.txt"; then
+    cp "../input/2021/day$i.txt" "corpus/day$i/input.txt"
+  fi
+done
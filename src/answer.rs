@@ -0,0 +1,95 @@
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+
+/// Unified return type of every puzzle part
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum Answer {
+    /// numeric answer fitting into a signed 64 bit integer
+    Int(i64),
+    /// numeric answer too large for `Int`
+    BigInt(u128),
+    /// plain text answer, e.g. a code read from the puzzle output
+    Text(String),
+    /// multiline text rendering of a grid which needs to be read by a human
+    Grid(String),
+}
+
+impl Display for Answer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Answer::Int(n) => write!(f, "{}", n),
+            Answer::BigInt(n) => write!(f, "{}", n),
+            Answer::Text(s) => write!(f, "{}", s),
+            // grids start on their own line so they line up when printed after a label
+            Answer::Grid(s) => write!(f, "\n{}", s),
+        }
+    }
+}
+
+impl From<i64> for Answer {
+    fn from(n: i64) -> Self {
+        Answer::Int(n)
+    }
+}
+
+impl From<u32> for Answer {
+    fn from(n: u32) -> Self {
+        Answer::Int(n as i64)
+    }
+}
+
+impl From<u64> for Answer {
+    fn from(n: u64) -> Self {
+        match i64::try_from(n) {
+            Ok(n) => Answer::Int(n),
+            Err(_) => Answer::BigInt(n as u128),
+        }
+    }
+}
+
+impl From<usize> for Answer {
+    fn from(n: usize) -> Self {
+        Answer::from(n as u64)
+    }
+}
+
+impl From<u128> for Answer {
+    fn from(n: u128) -> Self {
+        match i64::try_from(n) {
+            Ok(n) => Answer::Int(n),
+            Err(_) => Answer::BigInt(n),
+        }
+    }
+}
+
+impl From<String> for Answer {
+    fn from(s: String) -> Self {
+        Answer::Text(s)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(s: &str) -> Self {
+        Answer::Text(s.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conversions() {
+        assert_eq!(Answer::from(7usize), Answer::Int(7));
+        assert_eq!(Answer::from(u64::MAX), Answer::BigInt(u64::MAX as u128));
+        assert_eq!(Answer::from("ABC"), Answer::Text("ABC".into()));
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(Answer::Int(-3).to_string(), "-3");
+        assert_eq!(Answer::Text("ABC".into()).to_string(), "ABC");
+        assert_eq!(Answer::Grid("#.\n.#".into()).to_string(), "\n#.\n.#");
+    }
+}
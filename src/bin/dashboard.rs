@@ -0,0 +1,344 @@
+use adventofcode_rust_2021::answer::Answer;
+use adventofcode_rust_2021::puzzle::{puzzles, Puzzle};
+use adventofcode_rust_2021::runner::{animations, load_example, load_input, solutions, Run};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use std::process::Command;
+use std::time::Duration;
+
+/// delay between two animation frames, also the polling interval for key presses
+const TICK: Duration = Duration::from_millis(100);
+
+const HELP: &str = "↑↓ day  1/2 run part  i input  d description  a animate  space pause  \
+                    ←→ frame  PgUp/PgDn scroll  t test  q quit";
+
+/// Interactive terminal dashboard to browse the days, run their parts on the example or the real
+/// input, read the puzzle descriptions and watch the simulation of grid days
+fn main() -> anyhow::Result<()> {
+    let mut terminal = ratatui::init();
+    let result = App::new().run(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Source {
+    Example,
+    Real,
+}
+
+enum View {
+    Description,
+    Animation {
+        frames: Vec<String>,
+        frame: usize,
+        playing: bool,
+    },
+    Grid(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TestStatus {
+    NotRun,
+    Passed,
+    Failed,
+}
+
+/// Last results of a day in this session
+struct Day {
+    puzzle: Puzzle,
+    runs: [Option<(Source, Result<Run, String>)>; 2],
+    tests: TestStatus,
+}
+
+struct App {
+    days: Vec<Day>,
+    list: ListState,
+    source: Source,
+    view: View,
+    scroll: u16,
+    message: String,
+}
+
+impl App {
+    fn new() -> App {
+        let days = puzzles()
+            .into_iter()
+            .map(|puzzle| Day {
+                puzzle,
+                runs: [None, None],
+                tests: TestStatus::NotRun,
+            })
+            .collect();
+        App {
+            days,
+            list: ListState::default().with_selected(Some(0)),
+            source: Source::Example,
+            view: View::Description,
+            scroll: 0,
+            message: String::new(),
+        }
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> anyhow::Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            if !event::poll(TICK)? {
+                self.tick();
+                continue;
+            }
+            let key = match event::read()? {
+                Event::Key(key) if key.kind == KeyEventKind::Press => key,
+                _ => continue,
+            };
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Up | KeyCode::Char('k') => self.select(-1),
+                KeyCode::Down | KeyCode::Char('j') => self.select(1),
+                KeyCode::Char('1') => self.run_part(1),
+                KeyCode::Char('2') => self.run_part(2),
+                KeyCode::Char('i') => {
+                    self.source = match self.source {
+                        Source::Example => Source::Real,
+                        Source::Real => Source::Example,
+                    }
+                }
+                KeyCode::Char('d') => self.show(View::Description),
+                KeyCode::Char('a') => self.animate(),
+                KeyCode::Char(' ') => {
+                    if let View::Animation { playing, .. } = &mut self.view {
+                        *playing = !*playing;
+                    }
+                }
+                KeyCode::Left => self.step_frame(-1),
+                KeyCode::Right => self.step_frame(1),
+                KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(10),
+                KeyCode::PageDown => self.scroll = self.scroll.saturating_add(10),
+                KeyCode::Char('t') => {
+                    // tests take a while, show that they are running before blocking
+                    self.message = "running tests...".into();
+                    terminal.draw(|frame| self.draw(frame))?;
+                    self.run_tests();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn selected(&self) -> &Day {
+        &self.days[self.list.selected().unwrap_or_default()]
+    }
+
+    fn select(&mut self, offset: isize) {
+        let current = self.list.selected().unwrap_or_default() as isize;
+        let next = (current + offset).clamp(0, self.days.len() as isize - 1);
+        self.list.select(Some(next as usize));
+        self.message.clear();
+        self.show(View::Description);
+    }
+
+    fn show(&mut self, view: View) {
+        self.view = view;
+        self.scroll = 0;
+    }
+
+    fn input(&self) -> Result<String, String> {
+        let day = self.selected().puzzle.day;
+        let input = match self.source {
+            Source::Example => load_example(day),
+            Source::Real => load_input(day),
+        };
+        input.map_err(|e| format!("no {:?} input for day {}: {}", self.source, day, e))
+    }
+
+    fn run_part(&mut self, part: u8) {
+        let day = self.selected().puzzle.day;
+        let solution = match solutions()
+            .into_iter()
+            .find(|s| s.day == day && s.part == part)
+        {
+            Some(solution) => solution,
+            None => {
+                self.message = format!("part {} of day {} is not solved yet", part, day);
+                return;
+            }
+        };
+        let run = self
+            .input()
+            .and_then(|input| solution.run(&input).map_err(|e| format!("{:#}", e)));
+        if let Ok(Run {
+            answer: Answer::Grid(grid),
+            ..
+        }) = &run
+        {
+            self.show(View::Grid(grid.clone()));
+        }
+        let index = self.list.selected().unwrap_or_default();
+        self.days[index].runs[part as usize - 1] = Some((self.source, run));
+    }
+
+    fn run_tests(&mut self) {
+        let day = self.selected().puzzle.day;
+        let filter = format!("day{}::", day);
+        let output = Command::new("cargo")
+            .args(["test", "--quiet", "--lib", &filter])
+            .output();
+        let status = match output {
+            Ok(output) if output.status.success() => TestStatus::Passed,
+            _ => TestStatus::Failed,
+        };
+        let index = self.list.selected().unwrap_or_default();
+        self.days[index].tests = status;
+        self.message.clear();
+    }
+
+    fn animate(&mut self) {
+        let day = self.selected().puzzle.day;
+        let animation = match animations().into_iter().find(|a| a.day == day) {
+            Some(animation) => animation,
+            None => {
+                self.message = format!("day {} has no simulation to watch", day);
+                return;
+            }
+        };
+        match self
+            .input()
+            .and_then(|input| animation.frames(&input).map_err(|e| format!("{:#}", e)))
+        {
+            Ok(frames) => self.show(View::Animation {
+                frames,
+                frame: 0,
+                playing: true,
+            }),
+            Err(e) => self.message = e,
+        }
+    }
+
+    /// advances a playing animation, stopping at its last frame
+    fn tick(&mut self) {
+        if let View::Animation {
+            frames,
+            frame,
+            playing: true,
+        } = &mut self.view
+        {
+            if *frame + 1 < frames.len() {
+                *frame += 1;
+            }
+        }
+    }
+
+    fn step_frame(&mut self, offset: isize) {
+        if let View::Animation {
+            frames,
+            frame,
+            playing,
+        } = &mut self.view
+        {
+            *playing = false;
+            *frame = (*frame as isize + offset).clamp(0, frames.len() as isize - 1) as usize;
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [days, main] =
+            Layout::horizontal([Constraint::Length(36), Constraint::Min(0)]).areas(frame.area());
+        let [details, body, help] = Layout::vertical([
+            Constraint::Length(7),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .areas(main);
+
+        self.draw_days(frame, days);
+        self.draw_details(frame, details);
+        self.draw_body(frame, body);
+        frame.render_widget(
+            Paragraph::new(HELP).style(Style::default().fg(Color::DarkGray)),
+            help,
+        );
+    }
+
+    fn draw_days(&mut self, frame: &mut Frame, area: Rect) {
+        let items: Vec<ListItem> = self
+            .days
+            .iter()
+            .map(|day| {
+                let tests = match day.tests {
+                    TestStatus::NotRun => " ",
+                    TestStatus::Passed => "✓",
+                    TestStatus::Failed => "✗",
+                };
+                ListItem::new(format!(
+                    "{:>2} {:<27} {}",
+                    day.puzzle.day,
+                    day.puzzle.title(),
+                    tests
+                ))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(Block::bordered().title(" Advent of Code 2021 "))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, area, &mut self.list);
+    }
+
+    fn draw_details(&self, frame: &mut Frame, area: Rect) {
+        let day = self.selected();
+        let mut lines = vec![Line::from(format!("input: {:?}", self.source))];
+        for (part, run) in day.runs.iter().enumerate() {
+            let text = match run {
+                None => "not run".to_string(),
+                Some((source, Ok(run))) => {
+                    let answer = match &run.answer {
+                        Answer::Grid(_) => "grid, shown below".to_string(),
+                        answer => answer.to_string(),
+                    };
+                    format!(
+                        "{} ({:?}, generator {:?}, runner {:?})",
+                        answer, source, run.generator, run.runner
+                    )
+                }
+                Some((source, Err(e))) => format!("failed on {:?} input: {}", source, e),
+            };
+            lines.push(Line::from(format!("part {}: {}", part + 1, text)));
+        }
+        let tests = match day.tests {
+            TestStatus::NotRun => "not run",
+            TestStatus::Passed => "passed",
+            TestStatus::Failed => "failed",
+        };
+        lines.push(Line::from(format!("tests: {}", tests)));
+        lines.push(Line::from(self.message.as_str()).style(Style::default().fg(Color::Yellow)));
+        let title = format!(" Day {}: {} ", day.puzzle.day, day.puzzle.title());
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::bordered().title(title)),
+            area,
+        );
+    }
+
+    fn draw_body(&self, frame: &mut Frame, area: Rect) {
+        let paragraph = match &self.view {
+            View::Description => Paragraph::new(self.selected().puzzle.description())
+                .wrap(Wrap { trim: false })
+                .block(Block::bordered().title(" Description ")),
+            View::Animation {
+                frames,
+                frame,
+                playing,
+            } => {
+                let state = if *playing { "playing" } else { "paused" };
+                let title = format!(" Step {} of {} ({}) ", frame, frames.len() - 1, state);
+                Paragraph::new(frames[*frame].as_str()).block(Block::bordered().title(title))
+            }
+            View::Grid(grid) => {
+                Paragraph::new(grid.as_str()).block(Block::bordered().title(" Answer "))
+            }
+        };
+        frame.render_widget(paragraph.scroll((self.scroll, 0)), area);
+    }
+}
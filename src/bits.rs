@@ -0,0 +1,294 @@
+//! Bit streams for binary puzzle formats
+//!
+//! `Bits` owns a sequence of bits whose length need not be a multiple of 8. It is read with a
+//! `BitReader` and built with a `BitWriter`. Both handle values of up to 64 bits, most
+//! significant bit first, like the binary numbers in the puzzle descriptions.
+
+use std::fmt::{Display, Formatter};
+
+/// An owned sequence of bits
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Bits {
+    bytes: Vec<u8>,
+    len: usize,
+}
+
+impl Bits {
+    /// bits of hexadecimal digits, 4 per digit
+    pub fn from_hex(hex: &str) -> anyhow::Result<Bits> {
+        let mut writer = BitWriter::new();
+        for (idx, c) in hex.chars().enumerate() {
+            let digit = c
+                .to_digit(16)
+                .ok_or_else(|| anyhow!("invalid hex digit `{}` at position {}", c, idx))?;
+            writer.write(digit as u64, 4);
+        }
+        Ok(writer.into_bits())
+    }
+
+    /// bits of a string of `0` and `1`
+    pub fn from_binary(binary: &str) -> anyhow::Result<Bits> {
+        let mut writer = BitWriter::new();
+        for (idx, c) in binary.chars().enumerate() {
+            match c {
+                '0' => writer.write_bit(false),
+                '1' => writer.write_bit(true),
+                _ => bail!("invalid binary digit `{}` at position {}", c, idx),
+            }
+        }
+        Ok(writer.into_bits())
+    }
+
+    /// all bits of the bytes, starting with the most significant bit of the first one
+    pub fn from_bytes(bytes: &[u8]) -> Bits {
+        Bits {
+            bytes: bytes.to_vec(),
+            len: bytes.len() * 8,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// bit at given index, `None` beyond the end
+    pub fn get(&self, idx: usize) -> Option<bool> {
+        (idx < self.len).then(|| self.bytes[idx / 8] & (0x80 >> (idx % 8)) != 0)
+    }
+
+    /// reader starting at the first bit
+    pub fn reader(&self) -> BitReader<'_> {
+        BitReader {
+            bits: self,
+            start: 0,
+            end: self.len,
+            pos: 0,
+        }
+    }
+}
+
+/// shows the bits as string of `0` and `1`
+impl Display for Bits {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for idx in 0..self.len {
+            let digit = if self.get(idx) == Some(true) {
+                '1'
+            } else {
+                '0'
+            };
+            write!(f, "{}", digit)?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads consecutive values from a range of bits
+#[derive(Debug, Clone)]
+pub struct BitReader<'a> {
+    bits: &'a Bits,
+    start: usize,
+    end: usize,
+    pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    /// number of bits read so far, relative to the start of this reader
+    pub fn position(&self) -> usize {
+        self.pos - self.start
+    }
+
+    /// number of bits left to read
+    pub fn remaining(&self) -> usize {
+        self.end - self.pos
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pos == self.end
+    }
+
+    pub fn read_bit(&mut self) -> anyhow::Result<bool> {
+        Ok(self.read(1)? == 1)
+    }
+
+    /// reads an unsigned number of `width` bits, at most 64
+    pub fn read(&mut self, width: usize) -> anyhow::Result<u64> {
+        if width > 64 {
+            bail!("cannot read {} bits into a 64 bit number", width);
+        }
+        self.ensure(width)?;
+        let value = (self.pos..self.pos + width).fold(0, |value, idx| {
+            (value << 1) | self.bits.get(idx).unwrap() as u64
+        });
+        self.pos += width;
+        Ok(value)
+    }
+
+    /// skips over given number of bits
+    pub fn skip(&mut self, len: usize) -> anyhow::Result<()> {
+        self.ensure(len)?;
+        self.pos += len;
+        Ok(())
+    }
+
+    /// reader for the next `len` bits, which this reader skips over
+    ///
+    /// Positions of the sub-stream start at 0 again, and it cannot read beyond its `len` bits.
+    pub fn sub_stream(&mut self, len: usize) -> anyhow::Result<BitReader<'a>> {
+        self.ensure(len)?;
+        let sub = BitReader {
+            bits: self.bits,
+            start: self.pos,
+            end: self.pos + len,
+            pos: self.pos,
+        };
+        self.pos += len;
+        Ok(sub)
+    }
+
+    fn ensure(&self, len: usize) -> anyhow::Result<()> {
+        if len > self.remaining() {
+            bail!(
+                "cannot read {} bits at position {}, only {} left",
+                len,
+                self.position(),
+                self.remaining()
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Appends values to a growing sequence of bits
+#[derive(Debug, Clone, Default)]
+pub struct BitWriter {
+    bits: Bits,
+}
+
+impl BitWriter {
+    pub fn new() -> BitWriter {
+        BitWriter::default()
+    }
+
+    /// number of bits written so far
+    pub fn len(&self) -> usize {
+        self.bits.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits.is_empty()
+    }
+
+    pub fn write_bit(&mut self, bit: bool) {
+        let bits = &mut self.bits;
+        if bits.len.is_multiple_of(8) {
+            bits.bytes.push(0);
+        }
+        if bit {
+            bits.bytes[bits.len / 8] |= 0x80 >> (bits.len % 8);
+        }
+        bits.len += 1;
+    }
+
+    /// writes the lowest `width` bits of value, panics if the value does not fit into them
+    pub fn write(&mut self, value: u64, width: usize) {
+        assert!(
+            width <= 64 && (width == 64 || value >> width == 0),
+            "{} does not fit into {} bits",
+            value,
+            width
+        );
+        for shift in (0..width).rev() {
+            self.write_bit(value >> shift & 1 == 1);
+        }
+    }
+
+    pub fn into_bits(self) -> Bits {
+        self.bits
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn constructors() {
+        let bits = Bits::from_hex("D2FE28").unwrap();
+        assert_eq!(bits.to_string(), "110100101111111000101000");
+        assert_eq!(Bits::from_binary(&bits.to_string()).unwrap(), bits);
+        assert_eq!(Bits::from_bytes(&[0xD2, 0xFE, 0x28]), bits);
+        assert_eq!(Bits::from_binary("101").unwrap().len(), 3);
+        assert_eq!(
+            Bits::from_hex("1G").unwrap_err().to_string(),
+            "invalid hex digit `G` at position 1"
+        );
+        assert_eq!(
+            Bits::from_binary("012").unwrap_err().to_string(),
+            "invalid binary digit `2` at position 2"
+        );
+    }
+
+    #[test]
+    fn read_widths() {
+        let bits = Bits::from_hex("D2FE28").unwrap();
+        let mut reader = bits.reader();
+        assert_eq!(reader.read(3).unwrap(), 6);
+        assert_eq!(reader.read(3).unwrap(), 4);
+        assert!(reader.read_bit().unwrap());
+        assert_eq!(reader.position(), 7);
+        assert_eq!(reader.read(0).unwrap(), 0);
+        assert_eq!(reader.read(17).unwrap(), 0b0_1111_1110_0010_1000);
+        assert!(reader.is_empty());
+        assert_eq!(
+            reader.read(1).unwrap_err().to_string(),
+            "cannot read 1 bits at position 24, only 0 left"
+        );
+
+        let wide = Bits::from_bytes(&[0xff; 9]);
+        let mut reader = wide.reader();
+        assert!(reader.read(65).is_err());
+        assert_eq!(reader.read(64).unwrap(), u64::MAX);
+    }
+
+    #[test]
+    fn sub_streams() {
+        let bits = Bits::from_binary("1110000101").unwrap();
+        let mut reader = bits.reader();
+        reader.skip(2).unwrap();
+        let mut sub = reader.sub_stream(5).unwrap();
+        assert_eq!(reader.position(), 7);
+        assert_eq!(sub.remaining(), 5);
+        assert_eq!(sub.read(2).unwrap(), 0b10);
+        assert_eq!(sub.position(), 2);
+        assert!(sub.read(4).is_err());
+        assert_eq!(sub.read(3).unwrap(), 0);
+        assert_eq!(reader.read(3).unwrap(), 0b101);
+        assert!(reader.sub_stream(1).is_err());
+    }
+
+    #[test]
+    fn write_and_read_back() {
+        let mut writer = BitWriter::new();
+        writer.write(0b101, 3);
+        writer.write(u64::MAX, 64);
+        writer.write_bit(false);
+        writer.write(2021, 12);
+        assert_eq!(writer.len(), 80);
+        let bits = writer.into_bits();
+        let mut reader = bits.reader();
+        assert_eq!(reader.read(3).unwrap(), 0b101);
+        assert_eq!(reader.read(64).unwrap(), u64::MAX);
+        assert!(!reader.read_bit().unwrap());
+        assert_eq!(reader.read(12).unwrap(), 2021);
+    }
+
+    #[test]
+    #[should_panic(expected = "8 does not fit into 3 bits")]
+    fn write_overflow() {
+        BitWriter::new().write(8, 3);
+    }
+}
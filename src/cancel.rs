@@ -0,0 +1,94 @@
+//! Cooperative cancellation of long running solvers
+//!
+//! Solvers which may run for a long time check a `Cancel` token between units of their work.
+//! Once the token is cancelled, explicitly or because its time budget is used up, they stop with
+//! a `Cancelled` error describing the progress made so far.
+
+use std::fmt::{Display, Formatter};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Token to ask a solver to stop, shared by all of its clones
+#[derive(Debug, Clone, Default)]
+pub struct Cancel {
+    cancelled: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+}
+
+impl Cancel {
+    /// token which is only cancelled by calling `cancel`
+    pub fn new() -> Cancel {
+        Cancel::default()
+    }
+
+    /// token which is cancelled as soon as given time from now on has passed
+    pub fn with_budget(budget: Duration) -> Cancel {
+        Cancel {
+            cancelled: Arc::default(),
+            deadline: Instant::now().checked_add(budget),
+        }
+    }
+
+    /// asks every solver checking this token or one of its clones to stop
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+            || self
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    /// fails with the progress described by `progress` if the token is cancelled
+    pub fn check<P>(&self, progress: P) -> Result<(), Cancelled>
+    where
+        P: FnOnce() -> String,
+    {
+        match self.is_cancelled() {
+            true => Err(Cancelled {
+                progress: progress(),
+            }),
+            false => Ok(()),
+        }
+    }
+}
+
+/// A solver stopped before finding its answer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cancelled {
+    /// what the solver did until it was stopped, like `1200 steps simulated`
+    pub progress: String,
+}
+
+impl Display for Cancelled {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "cancelled after {}", self.progress)
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cancel_clones() {
+        let cancel = Cancel::new();
+        let clone = cancel.clone();
+        assert_eq!(clone.check(|| unreachable!()), Ok(()));
+        cancel.cancel();
+        let cancelled = clone.check(|| "3 steps simulated".into()).unwrap_err();
+        assert_eq!(cancelled.to_string(), "cancelled after 3 steps simulated");
+    }
+
+    #[test]
+    fn budgets() {
+        assert!(Cancel::with_budget(Duration::ZERO).is_cancelled());
+        assert!(!Cancel::with_budget(Duration::from_secs(3600)).is_cancelled());
+        assert!(!Cancel::with_budget(Duration::MAX).is_cancelled());
+    }
+}
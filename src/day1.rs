@@ -115,30 +115,62 @@
 //! Consider sums of a three-measurement sliding window.
 //! **How many sums are larger than the previous sum?**
 
+use crate::answer::Answer;
+use crate::explain::Explanation;
+use crate::sonar::{
+    analyze, count_increases, detect_anomalies, parse_sweep, Anomaly, AnomalyConfig,
+};
+
 #[aoc_generator(day1)]
-fn parse_input(input: &str) -> Vec<i64> {
-    input.lines().map(|line| line.parse().unwrap()).collect()
+pub(crate) fn parse_input(input: &str) -> anyhow::Result<Vec<i64>> {
+    input.lines().map(|line| Ok(line.parse()?)).collect()
 }
 
 /// Part 1: How many measurements are larger than the previous measurement?
 #[aoc(day1, part1)]
-fn part1(input: &[i64]) -> usize {
-    count_increases(input)
+pub(crate) fn part1(input: &[i64]) -> Answer {
+    count_increases(input.iter().copied(), 1).into()
 }
 
 /// Part 2: How many sums are larger than the previous sum?
 #[aoc(day1, part2)]
-fn part2(input: &[i64]) -> usize {
-    let threesome_sum: Vec<i64> = input.windows(3).map(|n| n.iter().sum()).collect();
-    count_increases(&threesome_sum)
+pub(crate) fn part2(input: &[i64]) -> Answer {
+    count_increases(input.iter().copied(), 3).into()
+}
+
+/// Anomaly mode: suspicious readings of the sweep, which unlike the puzzle input may miss
+/// readings as empty lines
+pub(crate) fn anomalies(input: &str) -> anyhow::Result<Vec<Anomaly>> {
+    Ok(detect_anomalies(
+        &parse_sweep(input)?,
+        &AnomalyConfig::default(),
+    ))
+}
+
+/// increases of single measurements
+pub(crate) fn explain1(input: &[i64]) -> Explanation {
+    explain_windows(input, 1)
+}
+
+/// increases of the three-measurement sums
+pub(crate) fn explain2(input: &[i64]) -> Explanation {
+    explain_windows(input, 3)
 }
 
-/// Counts how many values in collection increase compared to previous
-fn count_increases(collection: &[i64]) -> usize {
-    collection
-        .windows(2)
-        .filter(|window| window[1] > window[0])
-        .count()
+fn explain_windows(input: &[i64], window: usize) -> Explanation {
+    let report = analyze(input.iter().copied(), window);
+    let longest_run = match report.longest_run {
+        Some(run) => format!("windows {} to {}", run.start, run.end - 1),
+        None => "none".into(),
+    };
+    let largest_jump = match report.largest_jump {
+        Some(jump) => format!("{:+} at window {}", jump.delta, jump.index),
+        None => "none".into(),
+    };
+    Explanation::new()
+        .value("increases", report.increases.len())
+        .value("longest increasing run", longest_run)
+        .value("largest jump", largest_jump)
 }
 
 #[cfg(test)]
@@ -159,12 +191,56 @@ mod tests {
     #[test]
     fn part1_examples() {
         // In this example, there are `7` measurements that are larger than the previous measurement.
-        assert_eq!(part1(&parse_input(EXAMPLE)), 7);
+        assert_eq!(part1(&parse_input(EXAMPLE).unwrap()), Answer::Int(7));
     }
 
     #[test]
     fn part2_examples() {
         // In this example, there are `5` sums that are larger than the previous sum.
-        assert_eq!(part2(&parse_input(EXAMPLE)), 5);
+        assert_eq!(part2(&parse_input(EXAMPLE).unwrap()), Answer::Int(5));
+    }
+
+    #[test]
+    fn explain_examples() {
+        let input = parse_input(EXAMPLE).unwrap();
+        assert_eq!(
+            explain2(&input).to_string(),
+            "increases: 5
+longest increasing run: windows 3 to 7
+largest jump: +69 at window 5
+"
+        );
+        assert_eq!(
+            explain1(&input).to_string(),
+            "increases: 7
+longest increasing run: windows 0 to 3
+largest jump: +33 at window 6
+"
+        );
+    }
+
+    #[test]
+    fn anomaly_mode() {
+        let mut lines: Vec<&str> = EXAMPLE.lines().collect();
+        lines[4] = "100";
+        lines.insert(7, "");
+        lines.insert(7, "");
+        let found: Vec<String> = anomalies(&lines.join("\n"))
+            .unwrap()
+            .iter()
+            .map(|a| format!("{} {:?} {}", a.index, a.depth, a.reason))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                "4 Some(100) outlier from rolling median 207",
+                "4 Some(100) sudden drop from 210",
+                "7 None gap of 2 missing readings",
+            ]
+        );
+        assert_eq!(
+            anomalies("199\n\n200").unwrap()[0].reason.to_string(),
+            "gap of 1 missing reading"
+        );
     }
 }
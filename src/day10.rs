@@ -134,26 +134,31 @@
 //! Find the completion string for each incomplete line, score the completion strings, and sort
 //! the scores. **What is the middle score?**
 
+use crate::answer::Answer;
+
 /// Part 1:
 /// Find the first illegal character in each corrupted line of the navigation subsystem.
 /// What is the total syntax error score for those errors?
 #[aoc(day10, part1)]
-fn part1(input: &str) -> usize {
-    input.lines().map(syntax_error_score).sum()
+pub(crate) fn part1(input: &str) -> Answer {
+    input.lines().map(syntax_error_score).sum::<usize>().into()
 }
 
 /// Part 2:
 /// Find the completion string for each incomplete line, score the completion strings, and sort
 /// the scores. What is the middle score?
 #[aoc(day10, part2)]
-fn part2(input: &str) -> usize {
+pub(crate) fn part2(input: &str) -> anyhow::Result<Answer> {
     let mut scores: Vec<usize> = input
         .lines()
         .map(incomplete_score)
         .filter(|score| *score > 0)
         .collect();
+    if scores.is_empty() {
+        bail!("no incomplete lines");
+    }
     scores.sort_unstable();
-    scores[scores.len() / 2]
+    Ok(scores[scores.len() / 2].into())
 }
 
 /// To calculate the syntax error score for a line, take
@@ -208,12 +213,12 @@ fn incomplete_score(line: &str) -> usize {
 }
 
 #[derive(Debug, Clone, PartialEq)]
-enum LineStatus {
+pub(crate) enum LineStatus {
     Complete,
     Incomplete(Vec<char>),
 }
 
-fn check_line(line: &str) -> Result<LineStatus, (char, char)> {
+pub(crate) fn check_line(line: &str) -> Result<LineStatus, (char, char)> {
     let mut stack: Vec<char> = Vec::new();
     for char in line.chars() {
         match char {
@@ -285,7 +290,7 @@ mod tests {
         // `<{([([[(<>()){}]>(<<{{` - Expected `]`, but found `>` instead.
         assert_eq!(check_line("<{([([[(<>()){}]>(<<{{"), Err((']', '>')));
 
-        assert_eq!(part1(EXAMPLE), 26397);
+        assert_eq!(part1(EXAMPLE), Answer::Int(26397));
     }
 
     #[test]
@@ -301,6 +306,7 @@ mod tests {
         // <{([{{}}[<[[[<>{}]]]>[]] - Complete by adding ])}>.     - 294 total points.
         assert_eq!(incomplete_score("<{([{{}}[<[[[<>{}]]]>[]]"), 294);
 
-        assert_eq!(part2(EXAMPLE), 288957);
+        assert_eq!(part2(EXAMPLE).unwrap(), Answer::Int(288957));
+        assert_eq!(part2("()").unwrap_err().to_string(), "no incomplete lines");
     }
 }
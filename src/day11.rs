@@ -371,37 +371,64 @@
 //!
 //! **What is the first step during which all octopuses flash?**
 
+use crate::answer::Answer;
+use crate::cancel::Cancel;
+use crate::simulation::Simulation;
 use crate::util::{get_neighbors8, parse_array2};
 use ndarray::Array2;
 
 #[aoc_generator(day11)]
-fn parse_input(input: &str) -> Array2<u8> {
+pub(crate) fn parse_input(input: &str) -> anyhow::Result<Array2<u8>> {
     parse_array2(input)
 }
 
 /// Part 1: Given the starting energy levels of the dumbo octopuses in your cavern,
 /// simulate 100 steps. How many total flashes are there after 100 steps?
 #[aoc(day11, part1)]
-fn part1(input: &Array2<u8>) -> usize {
+pub(crate) fn part1(input: &Array2<u8>) -> Answer {
     let (flashes, _grid) = evolve(input, 100);
-    flashes
+    flashes.into()
 }
 
 /// Part 2: What is the first step during which all octopuses flash?
 #[aoc(day11, part2)]
-fn part2(input: &Array2<u8>) -> usize {
-    let mut step = 0;
-    let mut state = (*input).clone();
-    loop {
-        let (_, new_state) = evolve(&state, 1);
-        state = new_state;
-        step += 1;
-        if state.iter().map(|s| *s as u64).sum::<u64>() == 0 {
-            break;
-        }
+pub(crate) fn part2(input: &Array2<u8>) -> anyhow::Result<Answer> {
+    part2_cancellable(input, &Cancel::new())
+}
+
+/// part 2, stopping with a [`Cancelled`](crate::cancel::Cancelled) error and the number of steps simulated so far once
+/// cancelled
+pub(crate) fn part2_cancellable(input: &Array2<u8>, cancel: &Cancel) -> anyhow::Result<Answer> {
+    let steps = Simulation::new(input.clone(), |state| step(state).1).run_until_cancellable(
+        synchronized,
+        MAX_STEPS,
+        cancel,
+    )?;
+    match steps {
+        Some(steps) => Ok(steps.into()),
+        None => bail!(
+            "octopuses do not flash simultaneously within {} steps",
+            MAX_STEPS
+        ),
     }
+}
 
-    step
+/// upper bound for the steps until all octopuses flash simultaneously
+const MAX_STEPS: usize = 100_000;
+
+fn synchronized(energy_map: &Array2<u8>) -> bool {
+    energy_map.iter().all(|energy| *energy == 0)
+}
+
+/// simulates given number of steps, returning the total number of flashes and the energy
+/// levels afterwards
+fn evolve(energy_map: &Array2<u8>, steps: usize) -> (usize, Array2<u8>) {
+    let mut simulation = Simulation::new((0, energy_map.clone()), |(flashes, state)| {
+        let (step_flashes, next_state) = step(state);
+        (flashes + step_flashes, next_state)
+    });
+    simulation.run(steps);
+    simulation.into_state()
 }
 
 /// You can model the energy levels and flashes of light in steps. During a single step,
@@ -418,71 +445,84 @@ fn part2(input: &Array2<u8>) -> usize {
 ///
 /// Adjacent flashes can cause an octopus to flash on a step even if it begins that
 /// step with very little energy.
-fn evolve(energy_map: &Array2<u8>, steps: usize) -> (usize, Array2<u8>) {
-    let mut state = (*energy_map).clone();
-    let mut flashes = 0;
-    for _ in 0..steps {
-        let shape = energy_map.shape();
-        let mut next_state = Array2::zeros((shape[0], shape[1]));
-        let mut step_flashes: Vec<(usize, usize)> = Vec::new();
-        for (y, row) in state.rows().into_iter().enumerate() {
-            for (x, col) in row.iter().enumerate() {
-                if *col < 9 {
-                    next_state[(y, x)] = *col + 1;
-                } else {
-                    next_state[(y, x)] = 0;
-                    step_flashes.push((y, x));
-                }
+///
+/// Returns the number of flashes and the energy levels after the step.
+fn step(state: &Array2<u8>) -> (usize, Array2<u8>) {
+    let shape = state.shape();
+    let mut next_state = Array2::zeros((shape[0], shape[1]));
+    let mut step_flashes: Vec<(usize, usize)> = Vec::new();
+    for (y, row) in state.rows().into_iter().enumerate() {
+        for (x, col) in row.iter().enumerate() {
+            if *col < 9 {
+                next_state[(y, x)] = *col + 1;
+            } else {
+                next_state[(y, x)] = 0;
+                step_flashes.push((y, x));
             }
         }
-        let mut todo_flashes = step_flashes.clone();
-        while !todo_flashes.is_empty() {
-            let flash = todo_flashes.pop().unwrap();
-            for (y, x) in get_neighbors8(&state, &flash) {
-                if step_flashes.contains(&(y, x)) {
-                    continue;
-                }
-                let value = next_state[(y, x)];
-                if value < 9 {
-                    next_state[(y, x)] = value + 1;
-                } else {
-                    next_state[(y, x)] = 0;
-                    if !step_flashes.contains(&(y, x)) {
-                        todo_flashes.push((y, x));
-                        step_flashes.push((y, x));
-                    }
+    }
+    let mut todo_flashes = step_flashes.clone();
+    while let Some(flash) = todo_flashes.pop() {
+        for (y, x) in get_neighbors8(state, &flash) {
+            if step_flashes.contains(&(y, x)) {
+                continue;
+            }
+            let value = next_state[(y, x)];
+            if value < 9 {
+                next_state[(y, x)] = value + 1;
+            } else {
+                next_state[(y, x)] = 0;
+                if !step_flashes.contains(&(y, x)) {
+                    todo_flashes.push((y, x));
+                    step_flashes.push((y, x));
                 }
             }
         }
-        // println!("next state {:?}", next_state);
-        state = next_state;
-        flashes += step_flashes.len();
     }
-    (flashes, state)
+    // println!("next state {:?}", next_state);
+    (step_flashes.len(), next_state)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    fn string_evolve(energy_map: &Array2<u8>, steps: usize) -> (usize, String) {
-        let (flashes, grid) = evolve(energy_map, steps);
-        (flashes, stringify(&grid))
+/// energy levels of every step until all octopuses flash at once, at most `MAX_FRAMES`
+pub(crate) fn frames(input: &Array2<u8>) -> Vec<String> {
+    let mut frames = vec![stringify(input)];
+    let mut simulation = Simulation::new(input.clone(), |state| step(state).1);
+    while frames.len() < MAX_FRAMES && !synchronized(simulation.state()) {
+        frames.push(stringify(simulation.step()));
     }
+    frames
+}
 
-    fn stringify(energy_map: &Array2<u8>) -> String {
-        let mut out = String::new();
-        for (idx, row) in energy_map.rows().into_iter().enumerate() {
-            let mut line = String::new();
-            for (_, col) in row.iter().enumerate() {
-                line += &col.to_string();
-            }
-            out += &line;
-            if idx < energy_map.rows().into_iter().count() - 1 {
-                out += "\n";
-            }
+const MAX_FRAMES: usize = 1000;
+
+fn stringify(energy_map: &Array2<u8>) -> String {
+    let mut out = String::new();
+    for (idx, row) in energy_map.rows().into_iter().enumerate() {
+        let mut line = String::new();
+        for col in row.iter() {
+            line += &col.to_string();
+        }
+        out += &line;
+        if idx < energy_map.rows().into_iter().count() - 1 {
+            out += "\n";
         }
-        out
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cancel::Cancelled;
+    use crate::snapshot;
+
+    /// energy levels and total number of flashes after each of the steps
+    fn evolve_snapshot(energy_map: &Array2<u8>, steps: &[usize]) -> String {
+        snapshot::states(steps.iter().map(|&steps| {
+            let (flashes, grid) = evolve(energy_map, steps);
+            let label = format!("After step {} ({} flashes)", steps, flashes);
+            (label, stringify(&grid))
+        }))
     }
 
     const SMALL_EXAMPLE_0: &str = "11111
@@ -490,17 +530,6 @@ mod tests {
 19191
 19991
 11111";
-    const SMALL_EXAMPLE_1: &str = "34543
-40004
-50005
-40004
-34543";
-
-    const SMALL_EXAMPLE_2: &str = "45654
-51115
-61116
-51115
-45654";
 
     const EXAMPLE_0: &str = "5483143223
 2745854711
@@ -513,316 +542,49 @@ mod tests {
 4846848554
 5283751526";
 
-    const EXAMPLE_1: &str = "6594254334
-3856965822
-6375667284
-7252447257
-7468496589
-5278635756
-3287952832
-7993992245
-5957959665
-6394862637";
-
-    const EXAMPLE_2: &str = "8807476555
-5089087054
-8597889608
-8485769600
-8700908800
-6600088989
-6800005943
-0000007456
-9000000876
-8700006848";
-
-    const EXAMPLE_3: &str = "0050900866
-8500800575
-9900000039
-9700000041
-9935080063
-7712300000
-7911250009
-2211130000
-0421125000
-0021119000";
-
-    const EXAMPLE_4: &str = "2263031977
-0923031697
-0032221150
-0041111163
-0076191174
-0053411122
-0042361120
-5532241122
-1532247211
-1132230211";
-
-    const EXAMPLE_5: &str = "4484144000
-2044144000
-2253333493
-1152333274
-1187303285
-1164633233
-1153472231
-6643352233
-2643358322
-2243341322";
-
-    const EXAMPLE_6: &str = "5595255111
-3155255222
-3364444605
-2263444496
-2298414396
-2275744344
-2264583342
-7754463344
-3754469433
-3354452433";
-
-    const EXAMPLE_7: &str = "6707366222
-4377366333
-4475555827
-3496655709
-3500625609
-3509955566
-3486694453
-8865585555
-4865580644
-4465574644";
-
-    const EXAMPLE_8: &str = "7818477333
-5488477444
-5697666949
-4608766830
-4734946730
-4740097688
-6900007564
-0000009666
-8000004755
-6800007755";
-
-    const EXAMPLE_9: &str = "9060000644
-7800000976
-6900000080
-5840000082
-5858000093
-6962400000
-8021250009
-2221130009
-9111128097
-7911119976";
-
-    const EXAMPLE_10: &str = "0481112976
-0031112009
-0041112504
-0081111406
-0099111306
-0093511233
-0442361130
-5532252350
-0532250600
-0032240000";
-
-    const EXAMPLE_20: &str = "3936556452
-5686556806
-4496555690
-4448655580
-4456865570
-5680086577
-7000009896
-0000000344
-6000000364
-4600009543";
-
-    const EXAMPLE_30: &str = "0643334118
-4253334611
-3374333458
-2225333337
-2229333338
-2276733333
-2754574565
-5544458511
-9444447111
-7944446119";
-
-    const EXAMPLE_40: &str = "6211111981
-0421111119
-0042111115
-0003111115
-0003111116
-0065611111
-0532351111
-3322234597
-2222222976
-2222222762";
-
-    const EXAMPLE_50: &str = "9655556447
-4865556805
-4486555690
-4458655580
-4574865570
-5700086566
-6000009887
-8000000533
-6800000633
-5680000538";
-
-    const EXAMPLE_60: &str = "2533334200
-2743334640
-2264333458
-2225333337
-2225333338
-2287833333
-3854573455
-1854458611
-1175447111
-1115446111";
-
-    const EXAMPLE_70: &str = "8211111164
-0421111166
-0042111114
-0004211115
-0000211116
-0065611111
-0532351111
-7322235117
-5722223475
-4572222754";
-
-    const EXAMPLE_80: &str = "1755555697
-5965555609
-4486555680
-4458655580
-4570865570
-5700086566
-7000008666
-0000000990
-0000000800
-0000000000";
-
-    const EXAMPLE_90: &str = "7433333522
-2643333522
-2264333458
-2226433337
-2222433338
-2287833333
-2854573333
-4854458333
-3387779333
-3333333333";
-
-    const EXAMPLE_100: &str = "0397666866
-0749766918
-0053976933
-0004297822
-0004229892
-0053222877
-0532222966
-9322228966
-7922286866
-6789998766";
-
     #[test]
     fn part1_small_examples() {
-        assert_eq!(
-            string_evolve(&parse_input(SMALL_EXAMPLE_0), 1),
-            (9, SMALL_EXAMPLE_1.into()),
-        );
-        assert_eq!(
-            string_evolve(&parse_input(SMALL_EXAMPLE_0), 2),
-            (9, SMALL_EXAMPLE_2.into())
-        );
-        assert_eq!(
-            string_evolve(&parse_input(SMALL_EXAMPLE_1), 1),
-            (0, SMALL_EXAMPLE_2.into())
-        );
+        let input = parse_input(SMALL_EXAMPLE_0).unwrap();
+        insta::assert_snapshot!(evolve_snapshot(&input, &[0, 1, 2]));
     }
 
     #[test]
     fn part1_big_examples() {
+        let input = parse_input(EXAMPLE_0).unwrap();
+        let steps: Vec<usize> = (0..=10).chain((20..=100).step_by(10)).collect();
+        insta::assert_snapshot!(evolve_snapshot(&input, &steps));
+        // After 100 steps, there have been a total of 1656 flashes.
+        assert_eq!(part1(&input), Answer::Int(1656));
+    }
+
+    #[test]
+    fn part2_examples() {
+        // In the example above, the first time all octopuses flash simultaneously is step `195`:
         assert_eq!(
-            string_evolve(&parse_input(EXAMPLE_0), 1),
-            (0, EXAMPLE_1.into())
-        );
-        assert_eq!(
-            string_evolve(&parse_input(EXAMPLE_0), 2),
-            (35, EXAMPLE_2.into())
-        );
-        assert_eq!(
-            string_evolve(&parse_input(EXAMPLE_0), 3),
-            (80, EXAMPLE_3.into())
-        );
-        assert_eq!(
-            string_evolve(&parse_input(EXAMPLE_0), 4),
-            (96, EXAMPLE_4.into())
-        );
-        assert_eq!(
-            string_evolve(&parse_input(EXAMPLE_0), 5),
-            (104, EXAMPLE_5.into())
-        );
-        assert_eq!(
-            string_evolve(&parse_input(EXAMPLE_0), 6),
-            (105, EXAMPLE_6.into())
-        );
-        assert_eq!(
-            string_evolve(&parse_input(EXAMPLE_0), 7),
-            (112, EXAMPLE_7.into())
-        );
-        assert_eq!(
-            string_evolve(&parse_input(EXAMPLE_0), 8),
-            (136, EXAMPLE_8.into())
-        );
-        assert_eq!(
-            string_evolve(&parse_input(EXAMPLE_0), 9),
-            (175, EXAMPLE_9.into())
-        );
-        // After step 10, there have been a total of `204` flashes.
-        assert_eq!(
-            string_evolve(&parse_input(EXAMPLE_0), 10),
-            (204, EXAMPLE_10.into())
-        );
-        assert_eq!(
-            string_evolve(&parse_input(EXAMPLE_0), 20),
-            (344, EXAMPLE_20.into())
-        );
-        assert_eq!(
-            string_evolve(&parse_input(EXAMPLE_0), 30),
-            (513, EXAMPLE_30.into())
-        );
-        assert_eq!(
-            string_evolve(&parse_input(EXAMPLE_0), 40),
-            (703, EXAMPLE_40.into())
-        );
-        assert_eq!(
-            string_evolve(&parse_input(EXAMPLE_0), 50),
-            (842, EXAMPLE_50.into())
-        );
-        assert_eq!(
-            string_evolve(&parse_input(EXAMPLE_0), 60),
-            (1016, EXAMPLE_60.into())
-        );
-        assert_eq!(
-            string_evolve(&parse_input(EXAMPLE_0), 70),
-            (1202, EXAMPLE_70.into())
-        );
-        assert_eq!(
-            string_evolve(&parse_input(EXAMPLE_0), 80),
-            (1344, EXAMPLE_80.into())
-        );
-        assert_eq!(
-            string_evolve(&parse_input(EXAMPLE_0), 90),
-            (1505, EXAMPLE_90.into())
+            part2(&parse_input(EXAMPLE_0).unwrap()).unwrap(),
+            Answer::Int(195)
         );
-        // After 100 steps, there have been a total of 1656 flashes.
+
+        let cancel = Cancel::new();
+        cancel.cancel();
+        let error = part2_cancellable(&parse_input(EXAMPLE_0).unwrap(), &cancel).unwrap_err();
+        let cancelled = error.downcast::<Cancelled>().unwrap();
+        assert_eq!(cancelled.progress, "0 steps simulated");
+
+        // the two octopuses keep flashing in turns
         assert_eq!(
-            string_evolve(&parse_input(EXAMPLE_0), 100),
-            (1656, EXAMPLE_100.into())
+            part2(&parse_input("19").unwrap()).unwrap_err().to_string(),
+            "octopuses do not flash simultaneously within 100000 steps"
         );
     }
 
     #[test]
-    fn part2_examples() {
-        // In the example above, the first time all octopuses flash simultaneously is step `195`:
-        assert_eq!(part2(&parse_input(EXAMPLE_0)), 195);
+    fn frames_until_synchronized() {
+        let input = parse_input(EXAMPLE_0).unwrap();
+        let frames = frames(&input);
+        assert_eq!(frames.len(), 196);
+        assert_eq!(frames[0], EXAMPLE_0);
+        assert_eq!(frames[10], stringify(&evolve(&input, 10).1));
+        assert!(frames[195].chars().all(|c| c == '0' || c == '\n'));
     }
 }
@@ -184,83 +184,103 @@
 //!
 //! **Given these new rules, how many paths through this cave system are there?**
 
+use crate::answer::Answer;
+use crate::cancel::{Cancel, Cancelled};
+use crate::graph::NamedGraphBuilder;
 use petgraph::graph::NodeIndex;
 use petgraph::visit::EdgeRef;
 use petgraph::Graph;
-use std::cell::RefCell;
-use std::collections::HashMap;
-use std::sync::Arc;
+use rustc_hash::FxHashSet;
 
 #[aoc_generator(day12)]
-fn parse_input(input: &str) -> Graph<CaveNode, ()> {
-    let mut graph = Graph::new();
-    let mut node_by_name: HashMap<String, NodeIndex> = HashMap::new();
+pub(crate) fn parse_input(input: &str) -> anyhow::Result<Graph<CaveNode, ()>> {
+    let mut builder = NamedGraphBuilder::new(CaveNode::parse);
     for line in input.lines() {
-        let parts: Vec<&str> = line.split('-').collect();
-        let from_name = parts[0];
-        let to_name = parts[1];
-        if !node_by_name.contains_key(from_name) {
-            let idx = graph.add_node(CaveNode::parse(from_name));
-            node_by_name.insert(from_name.to_string(), idx);
+        let (from_name, to_name) = line
+            .split_once('-')
+            .ok_or_else(|| anyhow!("invalid connection: {}", line))?;
+        // paths could move back and forth between them forever
+        if let (CaveNode::BigCave(_), CaveNode::BigCave(_)) =
+            (CaveNode::parse(from_name)?, CaveNode::parse(to_name)?)
+        {
+            bail!("big caves connected directly: {}", line);
         }
-        if !node_by_name.contains_key(to_name) {
-            let idx = graph.add_node(CaveNode::parse(to_name));
-            node_by_name.insert(to_name.to_string(), idx);
-        }
-        let from_idx = node_by_name[from_name];
-        let to_idx = node_by_name[to_name];
-        graph.add_edge(from_idx, to_idx, ());
-        graph.add_edge(to_idx, from_idx, ());
+        builder.connect(from_name, to_name, ())?;
     }
-    graph
+    Ok(builder.build())
 }
 
 /// Part 1: How many paths through this cave system are there that visit small caves at most once?
 #[aoc(day12, part1)]
-fn part1(input: &Graph<CaveNode, ()>) -> usize {
-    build_paths(input, 1).len()
+pub(crate) fn part1(input: &Graph<CaveNode, ()>) -> Answer {
+    part1_cancellable(input, &Cancel::new()).expect("never cancelled")
+}
+
+/// part 1, stopping with the number of paths found so far once cancelled
+pub(crate) fn part1_cancellable(
+    input: &Graph<CaveNode, ()>,
+    cancel: &Cancel,
+) -> Result<Answer, Cancelled> {
+    Ok(build_paths(input, 1, cancel)?.len().into())
 }
 
 // /// Part 2: Given these new rules, how many paths through this cave system are there?
 // #[aoc(day12, part2)]
 // fn part2(input: &Graph<CaveNode, ()>) -> usize {
-//     build_paths(input, 2).len()
+//     build_paths(input, 2, &Cancel::new()).unwrap().len()
 // }
 
 #[derive(Clone, Debug)]
-enum CaveNode {
+pub(crate) enum CaveNode {
     Start,
     End,
-    SmallCave(String),
-    BigCave(String),
+    // cave names are only read when printing paths in tests
+    SmallCave(#[allow(dead_code)] String),
+    BigCave(#[allow(dead_code)] String),
 }
 
 impl CaveNode {
-    fn parse(name: &str) -> CaveNode {
-        match name {
+    fn parse(name: &str) -> anyhow::Result<CaveNode> {
+        Ok(match name {
             "start" => CaveNode::Start,
             "end" => CaveNode::End,
-            name => match name.chars().next().unwrap().is_lowercase() {
+            name => match name
+                .chars()
+                .next()
+                .ok_or_else(|| anyhow!("empty cave name"))?
+                .is_lowercase()
+            {
                 true => CaveNode::SmallCave(name.to_string()),
                 false => CaveNode::BigCave(name.to_string()),
             },
-        }
+        })
     }
 }
 
+/// descents between two checks for cancellation
+const CANCEL_INTERVAL: usize = 1024;
+
+/// Visits are counted per small cave in a table indexed by node, which is updated in place and
+/// restored after every descent instead of being cloned for each step. Complete paths are
+/// collected in `paths`, so they are kept when the search is cancelled.
 fn decend(
     graph: &Graph<CaveNode, ()>,
-    visited: Arc<RefCell<Vec<Vec<NodeIndex>>>>,
-    mut small_caves: HashMap<NodeIndex, u8>,
+    visited: &mut FxHashSet<Vec<NodeIndex>>,
+    small_caves: &mut [u8],
     max_small_caves: u8,
-    path: Vec<NodeIndex>,
-) -> Vec<Vec<NodeIndex>> {
-    let mut paths = Vec::new();
+    path: &mut Vec<NodeIndex>,
+    paths: &mut Vec<Vec<NodeIndex>>,
+    cancel: &Cancel,
+) -> Result<(), Cancelled> {
+    // every descent below the first ones starts with a new entry in `visited`
+    if visited.len() % CANCEL_INTERVAL == 0 {
+        cancel.check(|| format!("{} paths found", paths.len()))?;
+    }
     let last_idx = path[path.len() - 1];
     let last_node = graph.node_weight(last_idx).unwrap();
-    if let CaveNode::SmallCave(_) = last_node {
-        small_caves.entry(last_idx).or_insert(0);
-        *small_caves.get_mut(&last_idx).unwrap() += 1;
+    let small = matches!(last_node, CaveNode::SmallCave(_));
+    if small {
+        small_caves[last_idx.index()] += 1;
     }
     for edge in graph.edges(last_idx) {
         let target_idx = edge.target();
@@ -273,21 +293,17 @@ fn decend(
                 new_path.push(target_idx);
                 paths.push(new_path);
             }
-            CaveNode::SmallCave(_) => {
-                if small_caves.contains_key(&target_idx) {
-                    let cnt = small_caves[&target_idx];
-                    if cnt >= max_small_caves {
+            CaveNode::SmallCave(_) if small_caves[target_idx.index()] > 0 => {
+                let cnt = small_caves[target_idx.index()];
+                if cnt >= max_small_caves {
+                    continue;
+                } else if max_small_caves > 1 {
+                    let found_bad = small_caves
+                        .iter()
+                        .enumerate()
+                        .any(|(idx, &cnt)| idx != target_idx.index() && cnt >= max_small_caves);
+                    if found_bad {
                         continue;
-                    } else if max_small_caves > 1 {
-                        let mut found_bad = false;
-                        for key in small_caves.keys() {
-                            if *key != target_idx && small_caves[key] >= max_small_caves {
-                                found_bad = true;
-                            }
-                        }
-                        if found_bad {
-                            continue;
-                        }
                     }
                 }
             }
@@ -295,41 +311,52 @@ fn decend(
         };
         match target_node {
             CaveNode::SmallCave(_) | CaveNode::BigCave(_) => {
-                let mut new_path = path.clone();
-                new_path.push(target_idx);
-                if !visited.borrow().contains(&new_path) {
-                    visited.borrow_mut().push(new_path.clone());
-                    paths.append(&mut decend(
+                path.push(target_idx);
+                if !visited.contains(path.as_slice()) {
+                    visited.insert(path.clone());
+                    decend(
                         graph,
-                        visited.clone(),
-                        small_caves.clone(),
+                        visited,
+                        small_caves,
                         max_small_caves,
-                        new_path,
-                    ));
+                        path,
+                        paths,
+                        cancel,
+                    )?;
                 }
+                path.pop();
             }
             _ => {}
         };
     }
-    paths
+    if small {
+        small_caves[last_idx.index()] -= 1;
+    }
+    Ok(())
 }
 
-fn build_paths(graph: &Graph<CaveNode, ()>, max_small_caves: u8) -> Vec<Vec<NodeIndex>> {
+fn build_paths(
+    graph: &Graph<CaveNode, ()>,
+    max_small_caves: u8,
+    cancel: &Cancel,
+) -> Result<Vec<Vec<NodeIndex>>, Cancelled> {
     let mut paths = Vec::new();
-    let history = Arc::new(RefCell::new(Vec::new()));
+    let mut history = FxHashSet::default();
+    let mut small_caves = vec![0; graph.node_count()];
 
     for node_idx in graph.node_indices() {
         if let CaveNode::Start = graph.node_weight(node_idx).unwrap() {
             for edge in graph.edges(node_idx) {
                 let target_idx = edge.target();
-                let small_caves = HashMap::new();
-                paths.append(&mut decend(
+                decend(
                     graph,
-                    history.clone(),
-                    small_caves,
+                    &mut history,
+                    &mut small_caves,
                     max_small_caves,
-                    vec![node_idx, target_idx],
-                ));
+                    &mut vec![node_idx, target_idx],
+                    &mut paths,
+                    cancel,
+                )?;
             }
 
             // let mut dfs = Dfs::new(&graph, node_idx);
@@ -338,12 +365,13 @@ fn build_paths(graph: &Graph<CaveNode, ()>, max_small_caves: u8) -> Vec<Vec<Node
             break;
         }
     }
-    paths
+    Ok(paths)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::graph::to_dot;
 
     fn cave_name(cave_node: &CaveNode) -> &str {
         match cave_node {
@@ -362,6 +390,24 @@ b-d
 A-end
 b-end";
 
+    #[test]
+    fn dot_export() {
+        let graph = parse_input(EXAMPLE).unwrap();
+        let dot = to_dot(&graph, |n| cave_name(n).to_string(), |_| String::new());
+        assert!(dot.starts_with("digraph {\n    0 [label=\"start\"]\n    1 [label=\"A\"]\n"));
+        assert!(dot.contains("    0 -> 1\n    1 -> 0\n"));
+        assert_eq!(dot.lines().count(), 2 + 6 + 2 * 7);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            parse_input("start-A\nA-end\nA-B").unwrap_err().to_string(),
+            "big caves connected directly: A-B"
+        );
+        assert!(parse_input("start-A\nA-\nA-end").is_err());
+    }
+
     #[test]
     fn part1_examples() {
         let expected_paths = vec![
@@ -376,9 +422,10 @@ b-end";
             "start,b,A,end",
             "start,b,end",
         ];
-        let grid = parse_input(EXAMPLE);
+        let grid = parse_input(EXAMPLE).unwrap();
 
-        let actual_paths: Vec<String> = build_paths(&grid, 1)
+        let actual_paths: Vec<String> = build_paths(&grid, 1, &Cancel::new())
+            .unwrap()
             .iter()
             .map(|c| {
                 let foo: Vec<String> = c
@@ -402,6 +449,11 @@ b-end";
             }
         }
         assert_eq!(actual_paths.len(), 10);
+
+        let cancel = Cancel::new();
+        cancel.cancel();
+        let cancelled = part1_cancellable(&grid, &cancel).unwrap_err();
+        assert_eq!(cancelled.progress, "0 paths found");
     }
 
     #[test]
@@ -444,9 +496,10 @@ b-end";
             "start,b,d,b,end",
             "start,b,end",
         ];
-        let grid = parse_input(EXAMPLE);
+        let grid = parse_input(EXAMPLE).unwrap();
 
-        let actual_paths: Vec<String> = build_paths(&grid, 2)
+        let actual_paths: Vec<String> = build_paths(&grid, 2, &Cancel::new())
+            .unwrap()
             .iter()
             .map(|nodes| {
                 let path: Vec<String> = nodes
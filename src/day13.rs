@@ -149,11 +149,14 @@
 //!
 //! **What code do you use to activate the infrared thermal imaging camera system?**
 
+use crate::answer::Answer;
+use crate::ocr;
+use crate::validate::Violation;
 use ndarray::Array2;
 use regex::Regex;
 
 #[aoc_generator(day13)]
-fn parse_input(input: &str) -> Input {
+pub(crate) fn parse_input(input: &str) -> anyhow::Result<Input> {
     let re = Regex::new(r"^fold along (?P<x_or_y>[xy])=(?P<value>\d+)$").unwrap();
     let mut dots = Vec::new();
     let mut folds = Vec::new();
@@ -161,16 +164,23 @@ fn parse_input(input: &str) -> Input {
     let mut height = 0;
     for line in input.lines() {
         if let Some(matches) = re.captures(line) {
-            let value = matches.name("value").unwrap().as_str().parse().unwrap();
+            let value = matches.name("value").unwrap().as_str().parse()?;
+            if value >= MAX_PAPER_SIZE {
+                bail!("fold outside of paper: {}", line);
+            }
             match matches.name("x_or_y").unwrap().as_str() {
                 "x" => folds.push(Fold::FoldLeft(value)),
                 "y" => folds.push(Fold::FoldUp(value)),
                 _ => panic!("should not happen"),
             }
         } else if !line.is_empty() {
-            let parts: Vec<usize> = line.split(',').map(|c| c.parse().unwrap()).collect();
-            let x = parts[0];
-            let y = parts[1];
+            let (x, y) = line
+                .split_once(',')
+                .ok_or_else(|| anyhow!("invalid dot: {}", line))?;
+            let (x, y): (usize, usize) = (x.parse()?, y.parse()?);
+            if x >= MAX_PAPER_SIZE || y >= MAX_PAPER_SIZE {
+                bail!("dot outside of paper: {}", line);
+            }
             dots.push((x, y));
             if y > height {
                 height = y;
@@ -184,30 +194,144 @@ fn parse_input(input: &str) -> Input {
     for (x, y) in dots {
         grid[(y, x)] = true;
     }
+    // every fold has to cut the paper as left by the folds before it
+    let (mut height, mut width) = (height + 1, width + 1);
+    for fold in &folds {
+        let (axis, value, size) = match *fold {
+            Fold::FoldLeft(x) => ("x", x, &mut width),
+            Fold::FoldUp(y) => ("y", y, &mut height),
+        };
+        if value >= *size {
+            bail!("fold outside of paper: fold along {}={}", axis, value);
+        }
+        *size = value;
+    }
+
+    Ok(Input { grid, folds })
+}
 
-    Input { grid, folds }
+/// Checks dots and fold instructions are well-formed, that no dot lies on a fold line and that
+/// no fold drops dots because the folded part is larger than the part it is folded onto.
+pub(crate) fn validate(input: &str) -> Vec<Violation> {
+    let re = Regex::new(r"^fold along (?P<x_or_y>[xy])=(?P<value>\d+)$").unwrap();
+    let mut violations = Vec::new();
+    let mut dots = Vec::new();
+    let mut folds = 0;
+    for (idx, line) in input.lines().enumerate() {
+        let nr = idx + 1;
+        if let Some(matches) = re.captures(line) {
+            folds += 1;
+            let value: usize = match matches.name("value").unwrap().as_str().parse() {
+                Ok(value) => value,
+                Err(e) => {
+                    violations.push(Violation::at_line(
+                        nr,
+                        format!("invalid fold position: {}", e),
+                    ));
+                    continue;
+                }
+            };
+            let horizontal = matches.name("x_or_y").unwrap().as_str() == "y";
+            let on_line = dots
+                .iter()
+                .filter(|&&(x, y)| if horizontal { y == value } else { x == value })
+                .count();
+            if on_line > 0 {
+                violations.push(Violation::at_line(
+                    nr,
+                    format!("{} dots lie on the fold line", on_line),
+                ));
+            }
+            let dropped = dots
+                .iter()
+                .filter(|&&(x, y)| {
+                    if horizontal {
+                        y > 2 * value
+                    } else {
+                        x > 2 * value
+                    }
+                })
+                .count();
+            if dropped > 0 {
+                violations.push(Violation::at_line(
+                    nr,
+                    format!("{} dots are folded beyond the edge of the paper", dropped),
+                ));
+            }
+            for (x, y) in dots.iter_mut() {
+                let coord = if horizontal { y } else { x };
+                if *coord > value && *coord <= 2 * value {
+                    *coord = 2 * value - *coord;
+                }
+            }
+        } else if line.starts_with("fold") {
+            violations.push(Violation::at_line(
+                nr,
+                format!("invalid fold instruction `{}`", line),
+            ));
+        } else if !line.is_empty() {
+            if folds > 0 {
+                violations.push(Violation::at_line(nr, "dot after fold instructions"));
+            }
+            let parsed = line
+                .split_once(',')
+                .map(|(x, y)| (x.parse::<usize>(), y.parse::<usize>()));
+            match parsed {
+                Some((Ok(x), Ok(y))) if x < MAX_PAPER_SIZE && y < MAX_PAPER_SIZE => {
+                    dots.push((x, y))
+                }
+                Some((Ok(_), Ok(_))) => {
+                    violations.push(Violation::at_line(nr, "dot outside of paper"))
+                }
+                _ => violations.push(Violation::at_line(nr, format!("invalid dot `{}`", line))),
+            }
+        }
+    }
+    if dots.is_empty() {
+        violations.push(Violation::general("no dots found"));
+    }
+    if folds == 0 {
+        violations.push(Violation::general("no fold instructions found"));
+    }
+    violations
 }
 
 /// Part 1: How many dots are visible after completing just the first fold instruction on
 /// your transparent paper?
 #[aoc(day13, part1)]
-fn part1(input: &Input) -> usize {
+pub(crate) fn part1(input: &Input) -> Answer {
     let mut grid = input.grid.clone();
-    if let Some(fold) = input.folds.get(0) {
+    if let Some(fold) = input.folds.first() {
         grid = execute_fold(&grid, fold);
     }
-    grid.map(|f| if *f { 1 } else { 0 }).sum()
+    grid.iter().filter(|f| **f).count().into()
 }
 
 /// Part 2: What code do you use to activate the infrared thermal imaging camera system?
+///
+/// The folded paper shows the code in block letters. Papers which don't read as letters, like
+/// the square of the example, are returned as grid to be read by a human.
 #[aoc(day13, part2)]
-fn part2(input: &Input) -> String {
+pub(crate) fn part2(input: &Input) -> Answer {
     let mut grid = input.grid.clone();
     for fold in &input.folds {
         grid = execute_fold(&grid, fold);
     }
-    println!("{}", grid_str(&grid));
-    "BCZRCEAB".into()
+    match ocr::read(&grid) {
+        Ok(code) => Answer::Text(code),
+        Err(_) => Answer::Grid(grid_str(&grid)),
+    }
+}
+
+/// the paper before and after every fold
+pub(crate) fn frames(input: &Input) -> Vec<String> {
+    let mut grid = input.grid.clone();
+    let mut frames = vec![grid_str(&grid)];
+    for fold in &input.folds {
+        grid = execute_fold(&grid, fold);
+        frames.push(grid_str(&grid));
+    }
+    frames
 }
 
 fn execute_fold(grid: &Array2<bool>, fold: &Fold) -> Array2<bool> {
@@ -247,6 +371,9 @@ fn execute_fold(grid: &Array2<bool>, fold: &Fold) -> Array2<bool> {
     }
 }
 
+/// upper bound for both paper dimensions, real inputs stay well below it
+const MAX_PAPER_SIZE: usize = 1 << 11;
+
 #[derive(Debug, Clone)]
 enum Fold {
     FoldLeft(usize),
@@ -254,7 +381,7 @@ enum Fold {
 }
 
 #[derive(Debug, Clone)]
-struct Input {
+pub(crate) struct Input {
     grid: Array2<bool>,
     folds: Vec<Fold>,
 }
@@ -279,6 +406,7 @@ fn grid_str(grid: &Array2<bool>) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::snapshot;
 
     const EXAMPLE: &str = "6,10
 0,14
@@ -302,55 +430,64 @@ mod tests {
 fold along y=7
 fold along x=5";
 
-    const EXAMPLE_GRID: &str = "...#..#..#.
-....#......
-...........
-#..........
-...#....#.#
-...........
-...........
-...........
-...........
-...........
-.#....#.##.
-....#......
-......#...#
-#..........
-#.#........";
-
-    const EXAMPLE_GRID_FOLD_UP: &str = "#.##..#..#.
-#...#......
-......#...#
-#...#......
-.#.#..#.###
-...........
-...........";
-    const EXAMPLE_GRID_FOLD_LEFT: &str = "#####
-#...#
-#...#
-#...#
-#####
-.....
-.....";
-
     #[test]
     fn part1_examples() {
-        let input = parse_input(EXAMPLE);
-        assert_eq!(grid_str(&input.grid), EXAMPLE_GRID);
+        let input = parse_input(EXAMPLE).unwrap();
         assert_eq!(input.grid.shape(), [15, 11]);
         let folded1 = execute_fold(&input.grid, &Fold::FoldUp(7));
         assert_eq!(folded1.shape(), [7, 11]);
-        assert_eq!(grid_str(&folded1), EXAMPLE_GRID_FOLD_UP);
-        assert_eq!(part1(&input), 17);
+        assert_eq!(part1(&input), Answer::Int(17));
         let folded2 = execute_fold(&folded1, &Fold::FoldLeft(5));
         assert_eq!(folded2.shape(), [7, 5]);
-        assert_eq!(grid_str(&folded2), EXAMPLE_GRID_FOLD_LEFT);
+        let frames = frames(&input);
+        assert_eq!(frames[1], grid_str(&folded1));
+        assert_eq!(frames[2], grid_str(&folded2));
+        let labels = ["dots", "fold along y=7", "fold along x=5"];
+        insta::assert_snapshot!(snapshot::states(labels.into_iter().zip(frames)));
         // println!("input {:?}", input);
         // assert_eq!(0, part1(&input));
     }
 
-    // #[test]
-    // fn part2_examples() {
-    //     assert_eq!(0, part2(&parse_input(EXAMPLE)));
-    // }
+    #[test]
+    fn validate_examples() {
+        assert_eq!(validate(EXAMPLE), vec![]);
+        assert_eq!(
+            validate("6,10\n0,x\n9,7\n\nfold along y=7\nfold along x=2\nfold along z=1"),
+            vec![
+                Violation::at_line(2, "invalid dot `0,x`"),
+                Violation::at_line(5, "1 dots lie on the fold line"),
+                Violation::at_line(6, "2 dots are folded beyond the edge of the paper"),
+                Violation::at_line(7, "invalid fold instruction `fold along z=1`"),
+            ]
+        );
+    }
+
+    #[test]
+    fn part2_examples() {
+        let input = parse_input(EXAMPLE).unwrap();
+        let square = frames(&input).pop().unwrap();
+        assert_eq!(part2(&input), Answer::Grid(square));
+        // an F on the left half, folded onto itself
+        let dots = "0,0\n1,0\n2,0\n3,0\n0,1\n0,2\n1,2\n2,2\n0,3\n0,4\n0,5\n8,5";
+        let letters = parse_input(&format!("{}\n\nfold along x=4", dots)).unwrap();
+        assert_eq!(part2(&letters), Answer::Text("F".into()));
+    }
+
+    #[test]
+    fn parse_errors() {
+        let error = |input| parse_input(input).unwrap_err().to_string();
+        assert_eq!(error("1,2\n5000,1"), "dot outside of paper: 5000,1");
+        assert_eq!(
+            error("1,2\n\nfold along y=99999999999"),
+            "fold outside of paper: fold along y=99999999999"
+        );
+        assert_eq!(
+            error("1,1\n\nfold along y=5"),
+            "fold outside of paper: fold along y=5"
+        );
+        assert_eq!(
+            error("1,1\n3,3\n\nfold along x=1\nfold along x=5"),
+            "fold outside of paper: fold along x=5"
+        );
+    }
 }
@@ -89,12 +89,13 @@
 //! elements in the result. What do you get if you take the quantity of the most common
 //! element and subtract the quantity of the least common element?
 
-use std::collections::HashMap;
+use crate::answer::Answer;
+use crate::simulation::Simulation;
 
 // NNCB -> NNCHB
 
 #[aoc_generator(day14)]
-fn parse_input(input: &str) -> Polymer {
+pub(crate) fn parse_input(input: &str) -> anyhow::Result<Polymer> {
     let mut start = String::new();
     let mut rules: Vec<(char, char, char)> = Vec::new();
 
@@ -103,60 +104,82 @@ fn parse_input(input: &str) -> Polymer {
             0 => start = line.to_string(),
             1 => {}
             _ => {
-                let parts: Vec<String> = line.split(" -> ").map(|c| c.to_string()).collect();
-                rules.push((
-                    parts[0].chars().next().unwrap(),
-                    parts[0].chars().nth(1).unwrap(),
-                    parts[1].chars().next().unwrap(),
-                ));
+                let (pair, insert) = line
+                    .split_once(" -> ")
+                    .ok_or_else(|| anyhow!("invalid rule: {}", line))?;
+                let pair: Vec<char> = pair.chars().collect();
+                let insert: Vec<char> = insert.chars().collect();
+                match (pair.as_slice(), insert.as_slice()) {
+                    ([a, b], [c]) => rules.push((*a, *b, *c)),
+                    _ => bail!("invalid rule: {}", line),
+                }
             }
         }
     }
-    Polymer { rules, start }
+    let elements = start
+        .chars()
+        .chain(rules.iter().flat_map(|&(a, b, c)| [a, b, c]));
+    for element in elements {
+        if !element.is_ascii_uppercase() {
+            bail!("invalid element `{}`", element);
+        }
+    }
+    Ok(Polymer { rules, start })
 }
 
 /// Part 1: What do you get if you take the quantity of the most common element and subtract the
 /// quantity of the least common element?
 #[aoc(day14, part1)]
-fn part1(input: &Polymer) -> usize {
+pub(crate) fn part1(input: &Polymer) -> Answer {
     let str = evolve1(input, 10);
-    let frequencies = map_char_frequences(&str);
-    let min = *frequencies.values().min().unwrap();
-    let max = *frequencies.values().max().unwrap();
-    max - min
+    spread(&map_char_frequences(&str)).into()
 }
 
 /// Part 2
 #[aoc(day14, part2)]
-fn part2(input: &Polymer) -> usize {
-    let frequencies = evolve2(input, 40);
-    let min = *frequencies.values().min().unwrap();
-    let max = *frequencies.values().max().unwrap();
-    max - min
+pub(crate) fn part2(input: &Polymer) -> Answer {
+    spread(&evolve2(input, 40)).into()
+}
+
+/// number of possible elements, the letters `A` to `Z`
+const ELEMENTS: usize = 26;
+
+/// count per element, indexed by `element`
+type Frequencies = [usize; ELEMENTS];
+
+fn element(c: char) -> usize {
+    (c as u8 - b'A') as usize
 }
 
-fn map_char_frequences(input: &str) -> HashMap<char, usize> {
-    let mut map = HashMap::new();
+fn pair(a: char, b: char) -> usize {
+    element(a) * ELEMENTS + element(b)
+}
+
+fn map_char_frequences(input: &str) -> Frequencies {
+    let mut counts = [0; ELEMENTS];
     for char in input.chars() {
-        map.entry(char).or_insert(0);
-        *map.get_mut(&char).unwrap() += 1;
+        counts[element(char)] += 1;
     }
-    map
+    counts
+}
+
+/// quantity of the most common element minus the quantity of the least common one
+fn spread(frequencies: &Frequencies) -> usize {
+    let present = frequencies.iter().filter(|&&n| n > 0);
+    present.clone().max().unwrap_or(&0) - present.min().unwrap_or(&0)
 }
 
 fn evolve1(input: &Polymer, steps: usize) -> String {
     let mut state: Vec<char> = input.start.chars().collect();
     for _step in 0..steps {
         let mut next_state = state.clone();
-        let mut idx = 1;
         let mut inserts: Vec<(usize, char)> = Vec::new();
-        for w in state.windows(2) {
+        for (idx, w) in (1..).zip(state.windows(2)) {
             for rule in &input.rules {
                 if w[0] == rule.0 && w[1] == rule.1 {
                     inserts.push((idx, rule.2));
                 }
             }
-            idx += 1;
         }
         inserts.reverse();
         for (idx, char) in &inserts {
@@ -168,43 +191,39 @@ fn evolve1(input: &Polymer, steps: usize) -> String {
     String::from_iter(state)
 }
 
-fn evolve2(input: &Polymer, steps: usize) -> HashMap<char, usize> {
-    let mut frequencies = map_char_frequences(&input.start);
+/// Counts pairs instead of building the polymer, as every pair evolves independently of its
+/// position. Pairs and elements are counted in dense tables, which are cheap to copy per step.
+fn evolve2(input: &Polymer, steps: usize) -> Frequencies {
+    let mut insertions = [None; ELEMENTS * ELEMENTS];
+    for &(a, b, c) in &input.rules {
+        insertions[pair(a, b)] = Some(element(c));
+    }
     let chars: Vec<char> = input.start.chars().collect();
-    let windows: Vec<(char, char)> = chars.windows(2).map(|w| (w[0], w[1])).collect();
-    let mut window_map: HashMap<(char, char), usize> = HashMap::new();
-    for w in windows {
-        window_map.entry(w).or_insert(0);
-        *window_map.get_mut(&w).unwrap() += 1;
+    let mut pairs = [0; ELEMENTS * ELEMENTS];
+    for w in chars.windows(2) {
+        pairs[pair(w[0], w[1])] += 1;
     }
-    for _ in 0..steps {
-        let mut next_state = window_map.clone();
-
-        for (a, b, c) in &input.rules {
+    let start = (pairs, map_char_frequences(&input.start));
+    let mut simulation = Simulation::new(start, |(pairs, frequencies)| {
+        let mut next_state = *pairs;
+        let mut frequencies = *frequencies;
+        for (ab, &cnt) in pairs.iter().enumerate() {
             // AB -> C
-            let ab = (*a, *b);
-            let ac = (*a, *c);
-            let cb = (*c, *b);
-            if window_map.contains_key(&ab) && window_map[&ab] > 0 {
-                let cnt = window_map[&ab];
-                *next_state.get_mut(&ab).unwrap() -= cnt;
-
-                next_state.entry(ac).or_insert(0);
-                *next_state.get_mut(&ac).unwrap() += cnt;
-
-                next_state.entry(cb).or_insert(0);
-                *next_state.get_mut(&cb).unwrap() += cnt;
-
-                frequencies.entry(*c).or_insert(0);
-                *frequencies.get_mut(c).unwrap() += cnt;
+            if let (Some(c), true) = (insertions[ab], cnt > 0) {
+                let (a, b) = (ab / ELEMENTS, ab % ELEMENTS);
+                next_state[ab] -= cnt;
+                next_state[a * ELEMENTS + c] += cnt;
+                next_state[c * ELEMENTS + b] += cnt;
+                frequencies[c] += cnt;
             }
         }
-        window_map = next_state;
-    }
-    frequencies
+        (next_state, frequencies)
+    });
+    simulation.run(steps);
+    simulation.into_state().1
 }
 
-struct Polymer {
+pub(crate) struct Polymer {
     start: String,
     rules: Vec<(char, char, char)>,
 }
@@ -236,24 +255,28 @@ CN -> C";
     fn part1_examples() {
         // Template:     NNCB
         // After step 1: NCNBCHB
-        assert_eq!("NCNBCHB", evolve1(&parse_input(EXAMPLE), 1));
+        assert_eq!("NCNBCHB", evolve1(&parse_input(EXAMPLE).unwrap(), 1));
         // After step 2: NBCCNBBBCBHCB
-        assert_eq!("NBCCNBBBCBHCB", evolve1(&parse_input(EXAMPLE), 2));
+        assert_eq!("NBCCNBBBCBHCB", evolve1(&parse_input(EXAMPLE).unwrap(), 2));
         // After step 3: NBBBCNCCNBBNBNBBCHBHHBCHB
         assert_eq!(
             "NBBBCNCCNBBNBNBBCHBHHBCHB",
-            evolve1(&parse_input(EXAMPLE), 3)
+            evolve1(&parse_input(EXAMPLE).unwrap(), 3)
         );
         // After step 4: NBBNBNBBCCNBCNCCNBBNBBNBBBNBBNBBCBHCBHHNHCBBCBHCB
         assert_eq!(
             "NBBNBNBBCCNBCNCCNBBNBBNBBBNBBNBBCBHCBHHNHCBBCBHCB",
-            evolve1(&parse_input(EXAMPLE), 4)
+            evolve1(&parse_input(EXAMPLE).unwrap(), 4)
         );
-        assert_eq!(1588, part1(&parse_input(EXAMPLE)));
+        assert_eq!(Answer::Int(1588), part1(&parse_input(EXAMPLE).unwrap()));
+        assert!(parse_input("NNcB\n\nCH -> B").is_err());
     }
 
     #[test]
     fn part2_examples() {
-        assert_eq!(2188189693529, part2(&parse_input(EXAMPLE)));
+        assert_eq!(
+            Answer::Int(2188189693529),
+            part2(&parse_input(EXAMPLE).unwrap())
+        );
     }
 }
@@ -190,37 +190,67 @@
 //!
 //! **Using the full map, what is the lowest total risk of any path from the top left to the bottom right?**
 
-use crate::util::{array2_to_graph4, parse_array2, shape2};
+use crate::answer::Answer;
+use crate::cancel::{Cancel, Cancelled};
+use crate::explain::Explanation;
+use crate::pathfinding::{dial_cancellable, Path};
+use crate::util::{get_neighbors4, parse_array2, shape2};
 use ndarray::Array2;
-use petgraph::graph::NodeIndex;
 
 #[aoc_generator(day15)]
-fn parse_input(input: &str) -> Array2<u64> {
+pub(crate) fn parse_input(input: &str) -> anyhow::Result<Array2<u64>> {
     parse_array2(input)
 }
 
 /// Part 1: What is the lowest total risk of any path from the top left to the bottom right?
 #[aoc(day15, part1)]
-fn part1(grid: &Array2<u64>) -> u64 {
-    let (graph, node_grid) = array2_to_graph4(grid);
-    let (height, width) = shape2(grid);
-    let start: NodeIndex = node_grid[(0, 0)];
-    let goal: NodeIndex = node_grid[(height - 1, width - 1)];
-    let (cost, _) = petgraph::algo::astar(
-        &graph,
-        start,
-        |finish| finish == goal,
-        |e| *e.weight(),
-        |_| 0,
-    )
-    .unwrap();
-    cost
+pub(crate) fn part1(grid: &Array2<u64>) -> Answer {
+    part1_cancellable(grid, &Cancel::new()).expect("never cancelled")
+}
+
+/// part 1, stopping with the number of positions visited so far once cancelled
+pub(crate) fn part1_cancellable(grid: &Array2<u64>, cancel: &Cancel) -> Result<Answer, Cancelled> {
+    Ok(lowest_total_risk(grid, cancel)?.cost.into())
 }
 
 /// Part 2: Using the full map, what is the lowest total risk of any path from the
 /// top left to the bottom right?
 #[aoc(day15, part2)]
-fn part2(grid: &Array2<u64>) -> u64 {
+pub(crate) fn part2(grid: &Array2<u64>) -> Answer {
+    part2_cancellable(grid, &Cancel::new()).expect("never cancelled")
+}
+
+/// part 2, stopping with the number of positions visited so far once cancelled
+pub(crate) fn part2_cancellable(grid: &Array2<u64>, cancel: &Cancel) -> Result<Answer, Cancelled> {
+    Ok(lowest_total_risk(&full_map(grid), cancel)?.cost.into())
+}
+
+/// path with the lowest total risk through the scanned area
+pub(crate) fn explain1(grid: &Array2<u64>) -> Explanation {
+    let path = lowest_total_risk(grid, &Cancel::new()).expect("never cancelled");
+    Explanation::new()
+        .path("path", path.positions)
+        .value("lowest total risk", path.cost)
+}
+
+/// path with the lowest sum of risk levels from the top left to the bottom right, the risk
+/// level of the starting position is never entered and does not count
+fn lowest_total_risk(grid: &Array2<u64>, cancel: &Cancel) -> Result<Path, Cancelled> {
+    let (height, width) = shape2(grid);
+    let path = dial_cancellable(
+        grid,
+        (0, 0),
+        (height - 1, width - 1),
+        get_neighbors4,
+        |_, to| Some(grid[to]),
+        cancel,
+    )?;
+    Ok(path.expect("every position is reachable"))
+}
+
+/// The entire cave is actually five times larger in both dimensions than you thought; the area
+/// you originally scanned is just one tile in a 5x5 tile area that forms the full map.
+fn full_map(grid: &Array2<u64>) -> Array2<u64> {
     let (height, width) = shape2(grid);
     let mut full_grid: Array2<u64> = Array2::zeros((height * 5, width * 5));
     for (y, rows) in grid.rows().into_iter().enumerate() {
@@ -236,12 +266,22 @@ fn part2(grid: &Array2<u64>) -> u64 {
             }
         }
     }
-    part1(&full_grid)
+    full_grid
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::snapshot;
+
+    /// risk levels along the lowest risk path, `.` everywhere else
+    fn path_map(grid: &Array2<u64>) -> String {
+        let path = lowest_total_risk(grid, &Cancel::new()).unwrap();
+        snapshot::grid(grid, |pos, risk| match path.positions.contains(&pos) {
+            true => char::from_digit(*risk as u32, 10).unwrap(),
+            false => '.',
+        })
+    }
 
     const EXAMPLE: &str = "1163751742
 1381373672
@@ -256,11 +296,32 @@ mod tests {
 
     #[test]
     fn part1_examples() {
-        assert_eq!(40, part1(&parse_input(EXAMPLE)));
+        insta::assert_snapshot!(path_map(&parse_input(EXAMPLE).unwrap()));
+        assert_eq!(Answer::Int(40), part1(&parse_input(EXAMPLE).unwrap()));
     }
 
     #[test]
     fn part2_examples() {
-        assert_eq!(315, part2(&parse_input(EXAMPLE)));
+        let full = full_map(&parse_input(EXAMPLE).unwrap());
+        insta::assert_snapshot!(snapshot::states([
+            (
+                "full map",
+                snapshot::grid(&full, |_, risk| char::from_digit(*risk as u32, 10).unwrap())
+            ),
+            ("lowest risk path", path_map(&full)),
+        ]));
+        assert_eq!(Answer::Int(315), part2(&parse_input(EXAMPLE).unwrap()));
+    }
+
+    #[test]
+    fn explain_examples() {
+        let explanation = explain1(&parse_input(EXAMPLE).unwrap());
+        assert_eq!(explanation.entries.len(), 2);
+        assert!(explanation
+            .to_string()
+            .starts_with("path (19 positions): (0,0) (1,0) (2,0) (2,1)"));
+        assert!(explanation
+            .to_string()
+            .ends_with("(9,9)\nlowest total risk: 40\n"));
     }
 }
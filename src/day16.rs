@@ -1,194 +1,324 @@
-// //! # [Day 16: Packet Decoder](https://adventofcode.com/2021/day/16)
-// //!
-// //! As you leave the cave and reach open waters, you receive a transmission from the Elves
-// //! back on the ship.
-// //!
-// //! The transmission was sent using the Buoyancy Interchange Transmission System (BITS), a method
-// //! of packing numeric expressions into a binary sequence. Your submarine's computer has saved the
-// //! transmission in [hexadecimal](https://en.wikipedia.org/wiki/Hexadecimal) (your puzzle input).
-// //!
-// //! The first step of decoding the message is to convert the hexadecimal representation into binary.
-// //! Each character of hexadecimal corresponds to four bits of binary data:
-// //!
-// //! ```plain
-// //! 0 = 0000
-// //! 1 = 0001
-// //! 2 = 0010
-// //! 3 = 0011
-// //! 4 = 0100
-// //! 5 = 0101
-// //! 6 = 0110
-// //! 7 = 0111
-// //! 8 = 1000
-// //! 9 = 1001
-// //! A = 1010
-// //! B = 1011
-// //! C = 1100
-// //! D = 1101
-// //! E = 1110
-// //! F = 1111
-// //!
-// //! ```
-// //!
-// //! The BITS transmission contains a single packet at its outermost layer which itself contains
-// //! many other packets. The hexadecimal representation of this packet might encode a few extra `0`
-// //! bits at the end; these are not part of the transmission and should be ignored.
-// //!
-// //! Every packet begins with a standard header: the first three bits encode the packet version,
-// //! and the next three bits encode the packet type ID. These two values are numbers; all numbers
-// //! encoded in any packet are represented as binary with the most significant bit first.
-// //! For example, a version encoded as the binary sequence `100` represents the number `4`.
-// //!
-// //! Packets with type ID `4` represent a literal value. Literal value packets encode a single
-// //! binary number. To do this, the binary number is padded with leading zeroes until its length
-// //! is a multiple of four bits, and then it is broken into groups of four bits. Each group is
-// //! prefixed by a `1` bit except the last group, which is prefixed by a `0` bit.
-// //! These groups of five bits immediately follow the packet header.
-// //!
-// //! For example, the hexadecimal string `D2FE28` becomes:
-// //!
-// //! ```plain
-// //! 110100101111111000101000
-// //! VVVTTTAAAAABBBBBCCCCC
-// //!
-// //! ```
-// //!
-// //! Below each bit is a label indicating its purpose:
-// //!
-// //! -   The three bits labeled `V` (`110`) are the packet version, `6`.
-// //! -   The three bits labeled `T` (`100`) are the packet type ID, `4`, which means the packet
-// //!     is a literal value.
-// //! -   The five bits labeled `A` (`10111`) start with a `1` (not the last group, keep reading)
-// //!     and contain the first four bits of the number, `0111`.
-// //! -   The five bits labeled `B` (`11110`) start with a `1` (not the last group, keep reading)
-// //!     and contain four more bits of the number, `1110`.
-// //! -   The five bits labeled `C` (`00101`) start with a `0` (last group, end of packet) and
-// //!     contain the last four bits of the number, `0101`.
-// //! -   The three unlabeled `0` bits at the end are extra due to the hexadecimal representation
-// //!     and should be ignored.
-// //!
-// //! So, this packet represents a literal value with binary representation `011111100101`,
-// //! which is `2021` in decimal.
-// //!
-// //! Every other type of packet (any packet with a type ID other than `4`) represent an operator
-// //! that performs some calculation on one or more sub-packets contained within. Right now, the
-// //! specific operations aren't important; focus on parsing the hierarchy of sub-packets.
-// //!
-// //! An operator packet contains one or more packets. To indicate which subsequent binary data
-// //! represents its sub-packets, an operator packet can use one of two modes indicated by the bit
-// //! immediately after the packet header; this is called the length type ID:
-// //!
-// //! -   If the length type ID is `0`, then the next 15 bits are a number that represents the
-// //!     total length in bits of the sub-packets contained by this packet.
-// //! -   If the length type ID is `1`, then the next 11 bits are a number that represents the
-// //!     number of sub-packets immediately contained by this packet.
-// //!
-// //! Finally, after the length type ID bit and the 15-bit or 11-bit field, the sub-packets appear.
-// //!
-// //! For example, here is an operator packet (hexadecimal string `38006F45291200`) with length
-// //! type ID `0` that contains two sub-packets:
-// //!
-// //! ```plain
-// //! 00111000000000000110111101000101001010010001001000000000
-// //! VVVTTTILLLLLLLLLLLLLLLAAAAAAAAAAABBBBBBBBBBBBBBBB
-// //!
-// //! ```
-// //!
-// //! -   The three bits labeled `V` (`001`) are the packet version, `1`.
-// //! -   The three bits labeled `T` (`110`) are the packet type ID, `6`, which means the
-// //!     packet is an operator.
-// //! -   The bit labeled `I` (`0`) is the length type ID, which indicates that the length is a
-// //!     15-bit number representing the number of bits in the sub-packets.
-// //! -   The 15 bits labeled `L` (`000000000011011`) contain the length of the sub-packets
-// //!     in bits, `27`.
-// //! -   The 11 bits labeled `A` contain the first sub-packet, a literal value representing
-// //!     the number `10`.
-// //! -   The 16 bits labeled `B` contain the second sub-packet, a literal value representing
-// //!     the number `20`.
-// //!
-// //! After reading 11 and 16 bits of sub-packet data, the total length indicated in `L` (27)
-// //! is reached, and so parsing of this packet stops.
-// //!
-// //! As another example, here is an operator packet (hexadecimal string `EE00D40C823060`)
-// //! with length type ID `1` that contains three sub-packets:
-// //!
-// //! ```plain
-// //! 11101110000000001101010000001100100000100011000001100000
-// //! VVVTTTILLLLLLLLLLLAAAAAAAAAAABBBBBBBBBBBCCCCCCCCCCC
-// //!
-// //! ```
-// //!
-// //! -   The three bits labeled `V` (`111`) are the packet version, `7`.
-// //! -   The three bits labeled `T` (`011`) are the packet type ID, `3`, which means
-// //!     the packet is an operator.
-// //! -   The bit labeled `I` (`1`) is the length type ID, which indicates that the length
-// //!     is a 11-bit number representing the number of sub-packets.
-// //! -   The 11 bits labeled `L` (`00000000011`) contain the number of sub-packets, `3`.
-// //! -   The 11 bits labeled `A` contain the first sub-packet, a literal value
-// //!     representing the number `1`.
-// //! -   The 11 bits labeled `B` contain the second sub-packet, a literal value
-// //!     representing the number `2`.
-// //! -   The 11 bits labeled `C` contain the third sub-packet, a literal value
-// //!     representing the number `3`.
-// //!
-// //! After reading 3 complete sub-packets, the number of sub-packets indicated in `L` (3) is
-// //! reached, and so parsing of this packet stops.
-// //!
-// //! For now, parse the hierarchy of the packets throughout the transmission and add up
-// //! all of the version numbers.
-// //!
-// //! Here are a few more examples of hexadecimal-encoded transmissions:
-// //!
-// //! -   `8A004A801A8002F478` represents an operator packet (version 4) which contains an
-// //!     operator packet (version 1) which contains an operator packet (version 5) which contains a
-// //!     literal value (version 6); this packet has a version sum of `16`.
-// //! -   `620080001611562C8802118E34` represents an operator packet (version 3) which contains two
-// //!     sub-packets; each sub-packet is an operator packet that contains two literal values.
-// //!     This packet has a version sum of `12`.
-// //! -   `C0015000016115A2E0802F182340` has the same structure as the previous example, but the
-// //!     outermost packet uses a different length type ID. This packet has a version sum of `23`.
-// //! -   `A0016C880162017C3686B18A3D4780` is an operator packet that contains an operator packet that
-// //!     contains an operator packet that contains five literal values; it has a version sum of `31`.
-// //!
-// //! Decode the structure of your hexadecimal-encoded BITS transmission;
-// //! **what do you get if you add up the version numbers in all packets?**
-//
-// #[aoc_generator(day16)]
-// fn parse_input(_input: &str) -> Transmission {
-//     todo!()
-// }
-//
-// /// Part 1: what do you get if you add up the version numbers in all packets?
-// #[aoc(day16, part1)]
-// fn part1(_input: &Transmission) -> usize {
-//     todo!();
-// }
-//
-// // fn decode(hex: &str) -> u64 {
-// //     // D2FE28
-// // }
-//
-// /// Part 2
-// // #[aoc(day16, part2)]
-// // fn part2(input: &str) -> usize {
-// //     todo!();
-// // }
-//
-// struct Transmission {}
-//
-// #[cfg(test)]
-// mod tests {
-//     use super::*;
-//
-//     const EXAMPLE: &str = "";
-//
-//     #[test]
-//     fn part1_examples() {
-//         assert_eq!(0, part1(&parse_input(EXAMPLE)));
-//     }
-//
-//     // #[test]
-//     // fn part2_examples() {
-//     //     assert_eq!(0, part2(&parse_input(EXAMPLE)));
-//     // }
-// }
+//! # [Day 16: Packet Decoder](https://adventofcode.com/2021/day/16)
+//!
+//! As you leave the cave and reach open waters, you receive a transmission from the Elves
+//! back on the ship.
+//!
+//! The transmission was sent using the Buoyancy Interchange Transmission System (BITS), a method
+//! of packing numeric expressions into a binary sequence. Your submarine's computer has saved the
+//! transmission in [hexadecimal](https://en.wikipedia.org/wiki/Hexadecimal) (your puzzle input).
+//!
+//! The first step of decoding the message is to convert the hexadecimal representation into binary.
+//! Each character of hexadecimal corresponds to four bits of binary data:
+//!
+//! ```plain
+//! 0 = 0000
+//! 1 = 0001
+//! 2 = 0010
+//! 3 = 0011
+//! 4 = 0100
+//! 5 = 0101
+//! 6 = 0110
+//! 7 = 0111
+//! 8 = 1000
+//! 9 = 1001
+//! A = 1010
+//! B = 1011
+//! C = 1100
+//! D = 1101
+//! E = 1110
+//! F = 1111
+//!
+//! ```
+//!
+//! The BITS transmission contains a single packet at its outermost layer which itself contains
+//! many other packets. The hexadecimal representation of this packet might encode a few extra `0`
+//! bits at the end; these are not part of the transmission and should be ignored.
+//!
+//! Every packet begins with a standard header: the first three bits encode the packet version,
+//! and the next three bits encode the packet type ID. These two values are numbers; all numbers
+//! encoded in any packet are represented as binary with the most significant bit first.
+//! For example, a version encoded as the binary sequence `100` represents the number `4`.
+//!
+//! Packets with type ID `4` represent a literal value. Literal value packets encode a single
+//! binary number. To do this, the binary number is padded with leading zeroes until its length
+//! is a multiple of four bits, and then it is broken into groups of four bits. Each group is
+//! prefixed by a `1` bit except the last group, which is prefixed by a `0` bit.
+//! These groups of five bits immediately follow the packet header.
+//!
+//! For example, the hexadecimal string `D2FE28` becomes:
+//!
+//! ```plain
+//! 110100101111111000101000
+//! VVVTTTAAAAABBBBBCCCCC
+//!
+//! ```
+//!
+//! Below each bit is a label indicating its purpose:
+//!
+//! -   The three bits labeled `V` (`110`) are the packet version, `6`.
+//! -   The three bits labeled `T` (`100`) are the packet type ID, `4`, which means the packet
+//!     is a literal value.
+//! -   The five bits labeled `A` (`10111`) start with a `1` (not the last group, keep reading)
+//!     and contain the first four bits of the number, `0111`.
+//! -   The five bits labeled `B` (`11110`) start with a `1` (not the last group, keep reading)
+//!     and contain four more bits of the number, `1110`.
+//! -   The five bits labeled `C` (`00101`) start with a `0` (last group, end of packet) and
+//!     contain the last four bits of the number, `0101`.
+//! -   The three unlabeled `0` bits at the end are extra due to the hexadecimal representation
+//!     and should be ignored.
+//!
+//! So, this packet represents a literal value with binary representation `011111100101`,
+//! which is `2021` in decimal.
+//!
+//! Every other type of packet (any packet with a type ID other than `4`) represent an operator
+//! that performs some calculation on one or more sub-packets contained within. Right now, the
+//! specific operations aren't important; focus on parsing the hierarchy of sub-packets.
+//!
+//! An operator packet contains one or more packets. To indicate which subsequent binary data
+//! represents its sub-packets, an operator packet can use one of two modes indicated by the bit
+//! immediately after the packet header; this is called the length type ID:
+//!
+//! -   If the length type ID is `0`, then the next 15 bits are a number that represents the
+//!     total length in bits of the sub-packets contained by this packet.
+//! -   If the length type ID is `1`, then the next 11 bits are a number that represents the
+//!     number of sub-packets immediately contained by this packet.
+//!
+//! Finally, after the length type ID bit and the 15-bit or 11-bit field, the sub-packets appear.
+//!
+//! For example, here is an operator packet (hexadecimal string `38006F45291200`) with length
+//! type ID `0` that contains two sub-packets:
+//!
+//! ```plain
+//! 00111000000000000110111101000101001010010001001000000000
+//! VVVTTTILLLLLLLLLLLLLLLAAAAAAAAAAABBBBBBBBBBBBBBBB
+//!
+//! ```
+//!
+//! -   The three bits labeled `V` (`001`) are the packet version, `1`.
+//! -   The three bits labeled `T` (`110`) are the packet type ID, `6`, which means the
+//!     packet is an operator.
+//! -   The bit labeled `I` (`0`) is the length type ID, which indicates that the length is a
+//!     15-bit number representing the number of bits in the sub-packets.
+//! -   The 15 bits labeled `L` (`000000000011011`) contain the length of the sub-packets
+//!     in bits, `27`.
+//! -   The 11 bits labeled `A` contain the first sub-packet, a literal value representing
+//!     the number `10`.
+//! -   The 16 bits labeled `B` contain the second sub-packet, a literal value representing
+//!     the number `20`.
+//!
+//! After reading 11 and 16 bits of sub-packet data, the total length indicated in `L` (27)
+//! is reached, and so parsing of this packet stops.
+//!
+//! As another example, here is an operator packet (hexadecimal string `EE00D40C823060`)
+//! with length type ID `1` that contains three sub-packets:
+//!
+//! ```plain
+//! 11101110000000001101010000001100100000100011000001100000
+//! VVVTTTILLLLLLLLLLLAAAAAAAAAAABBBBBBBBBBBCCCCCCCCCCC
+//!
+//! ```
+//!
+//! -   The three bits labeled `V` (`111`) are the packet version, `7`.
+//! -   The three bits labeled `T` (`011`) are the packet type ID, `3`, which means
+//!     the packet is an operator.
+//! -   The bit labeled `I` (`1`) is the length type ID, which indicates that the length
+//!     is a 11-bit number representing the number of sub-packets.
+//! -   The 11 bits labeled `L` (`00000000011`) contain the number of sub-packets, `3`.
+//! -   The 11 bits labeled `A` contain the first sub-packet, a literal value
+//!     representing the number `1`.
+//! -   The 11 bits labeled `B` contain the second sub-packet, a literal value
+//!     representing the number `2`.
+//! -   The 11 bits labeled `C` contain the third sub-packet, a literal value
+//!     representing the number `3`.
+//!
+//! After reading 3 complete sub-packets, the number of sub-packets indicated in `L` (3) is
+//! reached, and so parsing of this packet stops.
+//!
+//! For now, parse the hierarchy of the packets throughout the transmission and add up
+//! all of the version numbers.
+//!
+//! Here are a few more examples of hexadecimal-encoded transmissions:
+//!
+//! -   `8A004A801A8002F478` represents an operator packet (version 4) which contains an
+//!     operator packet (version 1) which contains an operator packet (version 5) which contains a
+//!     literal value (version 6); this packet has a version sum of `16`.
+//! -   `620080001611562C8802118E34` represents an operator packet (version 3) which contains two
+//!     sub-packets; each sub-packet is an operator packet that contains two literal values.
+//!     This packet has a version sum of `12`.
+//! -   `C0015000016115A2E0802F182340` has the same structure as the previous example, but the
+//!     outermost packet uses a different length type ID. This packet has a version sum of `23`.
+//! -   `A0016C880162017C3686B18A3D4780` is an operator packet that contains an operator packet that
+//!     contains an operator packet that contains five literal values; it has a version sum of `31`.
+//!
+//! Decode the structure of your hexadecimal-encoded BITS transmission;
+//! **what do you get if you add up the version numbers in all packets?**
+
+use crate::answer::Answer;
+use crate::bits::{BitReader, Bits};
+
+#[aoc_generator(day16)]
+pub(crate) fn parse_input(input: &str) -> anyhow::Result<Packet> {
+    let bits = Bits::from_hex(input.trim())?;
+    read_packet(&mut bits.reader(), 0)
+}
+
+/// Part 1: what do you get if you add up the version numbers in all packets?
+#[aoc(day16, part1)]
+pub(crate) fn part1(input: &Packet) -> Answer {
+    version_sum(input).into()
+}
+
+const LITERAL: u8 = 4;
+
+/// deepest nesting of operator packets read, keeping malicious transmissions off the stack
+const MAX_DEPTH: usize = 256;
+
+/// A packet of the transmission with its sub-packets
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Packet {
+    version: u8,
+    type_id: u8,
+    content: Content,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Content {
+    Literal(u64),
+    Operator(Vec<Packet>),
+}
+
+/// reads a packet nested in `depth` operator packets, leaving the reader right behind it
+fn read_packet(reader: &mut BitReader, depth: usize) -> anyhow::Result<Packet> {
+    if depth > MAX_DEPTH {
+        bail!(
+            "packet at position {} nested deeper than {} packets",
+            reader.position(),
+            MAX_DEPTH
+        );
+    }
+    let version = reader.read(3)? as u8;
+    let type_id = reader.read(3)? as u8;
+    let content = if type_id == LITERAL {
+        Content::Literal(read_literal(reader)?)
+    } else if reader.read_bit()? {
+        let count = reader.read(11)?;
+        let packets = (0..count)
+            .map(|_| read_packet(reader, depth + 1))
+            .collect::<anyhow::Result<_>>()?;
+        Content::Operator(packets)
+    } else {
+        let len = reader.read(15)? as usize;
+        let mut sub = reader.sub_stream(len)?;
+        let mut packets = Vec::new();
+        while !sub.is_empty() {
+            packets.push(read_packet(&mut sub, depth + 1)?);
+        }
+        Content::Operator(packets)
+    };
+    Ok(Packet {
+        version,
+        type_id,
+        content,
+    })
+}
+
+/// groups of 4 bits, each prefixed by a `1` bit except the last one
+fn read_literal(reader: &mut BitReader) -> anyhow::Result<u64> {
+    let mut value = 0u64;
+    loop {
+        let more = reader.read_bit()?;
+        if value.leading_zeros() < 4 {
+            bail!(
+                "literal value at position {} exceeds 64 bits",
+                reader.position()
+            );
+        }
+        value = value << 4 | reader.read(4)?;
+        if !more {
+            return Ok(value);
+        }
+    }
+}
+
+fn version_sum(packet: &Packet) -> u64 {
+    let nested = match &packet.content {
+        Content::Literal(_) => 0,
+        Content::Operator(packets) => packets.iter().map(version_sum).sum(),
+    };
+    packet.version as u64 + nested
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bits::BitWriter;
+
+    fn literal(version: u8, value: u64) -> Packet {
+        Packet {
+            version,
+            type_id: LITERAL,
+            content: Content::Literal(value),
+        }
+    }
+
+    #[test]
+    fn parse_examples() {
+        assert_eq!(parse_input("D2FE28").unwrap(), literal(6, 2021));
+        assert_eq!(
+            parse_input("38006F45291200").unwrap(),
+            Packet {
+                version: 1,
+                type_id: 6,
+                content: Content::Operator(vec![literal(6, 10), literal(2, 20)]),
+            }
+        );
+        assert_eq!(
+            parse_input("EE00D40C823060").unwrap(),
+            Packet {
+                version: 7,
+                type_id: 3,
+                content: Content::Operator(vec![literal(2, 1), literal(4, 2), literal(1, 3)]),
+            }
+        );
+        assert!(parse_input("D2FE").is_err());
+    }
+
+    #[test]
+    fn nesting_limit() {
+        // operator packets of one sub-packet each, around a single literal
+        let nested = |depth| {
+            let mut writer = BitWriter::new();
+            for _ in 0..depth {
+                writer.write(0, 6);
+                writer.write(1 << 11 | 1, 12);
+            }
+            writer.write(LITERAL as u64, 6);
+            writer.write(1, 5);
+            let bits = writer.into_bits();
+            read_packet(&mut bits.reader(), 0)
+        };
+        assert!(nested(MAX_DEPTH).is_ok());
+        assert_eq!(
+            nested(MAX_DEPTH + 1).unwrap_err().to_string(),
+            "packet at position 4626 nested deeper than 256 packets"
+        );
+    }
+
+    #[test]
+    fn part1_examples() {
+        let sums = [
+            ("8A004A801A8002F478", 16),
+            ("620080001611562C8802118E34", 12),
+            ("C0015000016115A2E0802F182340", 23),
+            ("A0016C880162017C3686B18A3D4780", 31),
+        ];
+        for (hex, sum) in sums {
+            assert_eq!(
+                part1(&parse_input(hex).unwrap()),
+                Answer::Int(sum),
+                "{}",
+                hex
+            );
+        }
+    }
+}
@@ -77,31 +77,94 @@
 //!
 //! **What do you get if you multiply your final horizontal position by your final depth?**
 
+use crate::answer::Answer;
+use crate::explain::Explanation;
+use crate::submarine::{execute, summarize, trajectory, Instr, Interpretation, State};
+use std::str::FromStr;
+
+/// Besides the commands of the puzzle, courses may contain `back X`, `surface` and blocks of
+/// commands which are repeated `N` times, up to [`MAX_REPETITIONS`] times including the
+/// repetitions of enclosing blocks:
+///
+/// ```plain
+/// repeat 3 {
+///   forward 2
+///   down 1
+/// }
+/// ```
 #[aoc_generator(day2)]
-fn parse_input(input: &str) -> Vec<Instr> {
-    input
-        .lines()
-        .map(|line| {
-            let parts: Vec<&str> = line.split(' ').collect();
-            match parts[0] {
-                "forward" => Instr::Forward(parts[1].parse().unwrap()),
-                "up" => Instr::Up(parts[1].parse().unwrap()),
-                "down" => Instr::Down(parts[1].parse().unwrap()),
-                _ => {
-                    panic!("invalid format")
+pub(crate) fn parse_input(input: &str) -> anyhow::Result<Vec<Instr>> {
+    let mut course = Vec::new();
+    // blocks which are not closed yet, with the line they start on and their repetitions
+    let mut open: Vec<(usize, usize, Vec<Instr>)> = Vec::new();
+    for (idx, line) in input.lines().enumerate() {
+        let line_nr = idx + 1;
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let instr = match words[..] {
+            [] => continue,
+            ["forward", amount] => Instr::Forward(number(amount, line_nr)?),
+            ["down", amount] => Instr::Down(number(amount, line_nr)?),
+            ["up", amount] => Instr::Up(number(amount, line_nr)?),
+            ["back", amount] => Instr::Back(number(amount, line_nr)?),
+            ["surface"] => Instr::Surface,
+            ["repeat", times, "{"] => {
+                let times: usize = number(times, line_nr)?;
+                let outer: usize = open.iter().map(|(_, times, _)| times).product();
+                if times.checked_mul(outer).is_none_or(|n| n > MAX_REPETITIONS) {
+                    bail!(
+                        "more than {} repetitions on line {}",
+                        MAX_REPETITIONS,
+                        line_nr
+                    );
                 }
+                open.push((line_nr, times, Vec::new()));
+                continue;
+            }
+            ["}"] => match open.pop() {
+                Some((_, times, block)) => Instr::Repeat(times, block),
+                None => bail!("unexpected `}}` on line {}", line_nr),
+            },
+            [command, ..] if COMMANDS.contains(&command) => {
+                bail!(
+                    "invalid `{}` command on line {}: {}",
+                    command,
+                    line_nr,
+                    line
+                )
             }
-        })
-        .collect()
+            [command, ..] => bail!("unknown command `{}` on line {}", command, line_nr),
+        };
+        match open.last_mut() {
+            Some((_, _, block)) => block.push(instr),
+            None => course.push(instr),
+        }
+    }
+    if let Some((line_nr, _, _)) = open.last() {
+        bail!("repeat block starting on line {} is never closed", line_nr);
+    }
+    Ok(course)
+}
+
+/// most repetitions of a command, counting those of all blocks it is nested in
+const MAX_REPETITIONS: usize = 10_000;
+
+const COMMANDS: [&str; 6] = ["forward", "down", "up", "back", "surface", "repeat"];
+
+/// amounts and repetitions are never negative, commands with the opposite direction exist
+fn number<T: FromStr>(word: &str, line_nr: usize) -> anyhow::Result<T> {
+    if word.starts_with('-') {
+        bail!("negative number `{}` on line {}", word, line_nr);
+    }
+    word.parse()
+        .map_err(|_| anyhow!("invalid number `{}` on line {}", word, line_nr))
 }
 
 /// Part 1:
 /// Calculate the horizontal position and depth you would have after following the planned course.
 /// What do you get if you multiply your final horizontal position by your final depth?
 #[aoc(day2, part1)]
-fn part1(input: &[Instr]) -> i64 {
-    let (x, y) = execute1(input);
-    x * y
+pub(crate) fn part1(input: &[Instr]) -> anyhow::Result<Answer> {
+    product(execute(input, &Interpretation::Direct)?)
 }
 
 /// Part 2:
@@ -109,48 +172,41 @@ fn part1(input: &[Instr]) -> i64 {
 /// you would have after following the planned course.
 /// What do you get if you multiply your final horizontal position by your final depth?
 #[aoc(day2, part2)]
-fn part2(input: &[Instr]) -> i64 {
-    let (x, y) = execute2(input);
-    x * y
+pub(crate) fn part2(input: &[Instr]) -> anyhow::Result<Answer> {
+    product(execute(input, &Interpretation::Aim)?)
 }
 
-fn execute1(instructions: &[Instr]) -> (i64, i64) {
-    let mut pos = (0, 0);
-    for instr in instructions {
-        match instr {
-            Instr::Forward(amount) => pos.0 += amount,
-            Instr::Up(amount) => pos.1 -= amount,
-            Instr::Down(amount) => pos.1 += amount,
-        }
-    }
-    pos
+/// final horizontal position multiplied by the final depth
+fn product(end: State) -> anyhow::Result<Answer> {
+    end.x
+        .checked_mul(end.depth)
+        .map(Answer::from)
+        .ok_or_else(|| anyhow!("product of {} and {} is out of range", end.x, end.depth))
 }
 
-fn execute2(instructions: &[Instr]) -> (i64, i64) {
-    let mut pos = (0, 0);
-    let mut aim = 0;
-    for instr in instructions {
-        match instr {
-            Instr::Forward(amount) => {
-                pos.0 += amount;
-                pos.1 += aim * amount;
-            }
-            Instr::Up(amount) => aim -= amount,
-            Instr::Down(amount) => aim += amount,
-        }
-    }
-    pos
+/// key figures of the course when `down` and `up` change the depth
+pub(crate) fn explain1(input: &[Instr]) -> anyhow::Result<Explanation> {
+    explain(input, &Interpretation::Direct)
+}
+
+/// key figures of the course when `down` and `up` change the aim
+pub(crate) fn explain2(input: &[Instr]) -> anyhow::Result<Explanation> {
+    explain(input, &Interpretation::Aim)
 }
 
-enum Instr {
-    Forward(i64),
-    Down(i64),
-    Up(i64),
+fn explain(input: &[Instr], interpretation: &Interpretation) -> anyhow::Result<Explanation> {
+    let summary = summarize(&trajectory(input, interpretation)?);
+    Ok(Explanation::new()
+        .value("horizontal position", summary.end.x)
+        .value("depth", summary.end.depth)
+        .value("maximum depth", summary.max_depth)
+        .value("distance travelled", format!("{:.2}", summary.distance)))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::submarine::{plan, Objective};
 
     const EXAMPLE: &str = "forward 5
 down 5
@@ -163,15 +219,123 @@ forward 2";
     fn part1_examples() {
         // After following these instructions, you would have a horizontal position
         // of `15` and a depth of `10`. (Multiplying these together produces `150`.)
-        let pos = execute1(&parse_input(EXAMPLE));
-        assert_eq!(pos, (15, 10));
+        let end = execute(&parse_input(EXAMPLE).unwrap(), &Interpretation::Direct).unwrap();
+        assert_eq!((end.x, end.depth), (15, 10));
     }
 
     #[test]
     fn part2_examples() {
         // After following these new instructions, you would have a horizontal position of `15` and
         // a depth of `60`. (Multiplying these produces `900`.)
-        let pos = execute2(&parse_input(EXAMPLE));
-        assert_eq!(pos, (15, 60));
+        let end = execute(&parse_input(EXAMPLE).unwrap(), &Interpretation::Aim).unwrap();
+        assert_eq!((end.x, end.depth), (15, 60));
+    }
+
+    #[test]
+    fn extended_commands() {
+        let course = parse_input(
+            "down 2
+repeat 2 {
+  forward 3
+
+  repeat 2 {
+    up 1
+  }
+}
+back 1
+surface",
+        )
+        .unwrap();
+        assert_eq!(
+            course,
+            [
+                Instr::Down(2),
+                Instr::Repeat(
+                    2,
+                    vec![Instr::Forward(3), Instr::Repeat(2, vec![Instr::Up(1)])]
+                ),
+                Instr::Back(1),
+                Instr::Surface,
+            ]
+        );
+        let end = execute(&course, &Interpretation::Aim).unwrap();
+        assert_eq!((end.x, end.depth, end.aim), (5, 0, 0));
+    }
+
+    #[test]
+    fn planned_course_round_trip() {
+        let mut course = plan(15, 60, Objective::LeastMagnitude).unwrap();
+        course.push(Instr::Repeat(2, vec![Instr::Back(1), Instr::Surface]));
+        let text: String = course.iter().map(|instr| format!("{}\n", instr)).collect();
+        assert_eq!(
+            text,
+            "down 4\nforward 15\nrepeat 2 {\n  back 1\n  surface\n}\n"
+        );
+        assert_eq!(parse_input(&text).unwrap(), course);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let error = |input| parse_input(input).unwrap_err().to_string();
+        assert_eq!(
+            error("forward 5\ndive 3"),
+            "unknown command `dive` on line 2"
+        );
+        assert_eq!(error("forward x"), "invalid number `x` on line 1");
+        assert_eq!(
+            error("up 1\nforward\n"),
+            "invalid `forward` command on line 2: forward"
+        );
+        assert_eq!(
+            error("surface 3"),
+            "invalid `surface` command on line 1: surface 3"
+        );
+        assert_eq!(error("repeat -1 {"), "negative number `-1` on line 1");
+        assert_eq!(
+            error("up -9223372036854775808"),
+            "negative number `-9223372036854775808` on line 1"
+        );
+        assert_eq!(
+            error("repeat 2 {\nup 1\nrepeat 3 {\n}"),
+            "repeat block starting on line 1 is never closed"
+        );
+        assert_eq!(error("up 1\n}"), "unexpected `}` on line 2");
+        assert_eq!(
+            error("repeat 10001 {\n}"),
+            "more than 10000 repetitions on line 1"
+        );
+        assert_eq!(
+            error("repeat 100 {\nrepeat 100 {\n}\nrepeat 101 {\n}\n}"),
+            "more than 10000 repetitions on line 4"
+        );
+        assert!(parse_input("repeat 0 {\nrepeat 99999 {\n}\n}").is_ok());
+    }
+
+    #[test]
+    fn overflows() {
+        let error = |input, part: fn(&[Instr]) -> anyhow::Result<Answer>| {
+            part(&parse_input(input).unwrap()).unwrap_err().to_string()
+        };
+        assert_eq!(
+            error("down 99999999999\nforward 99999999999", part1),
+            "product of 99999999999 and 99999999999 is out of range"
+        );
+        assert_eq!(
+            error("forward 9223372036854775807\nforward 1", part2),
+            "submarine leaves the range of 64 bit numbers"
+        );
+    }
+
+    #[test]
+    fn explain_examples() {
+        let input = parse_input(EXAMPLE).unwrap();
+        assert_eq!(
+            explain1(&input).unwrap().to_string(),
+            "horizontal position: 15\ndepth: 10\nmaximum depth: 10\ndistance travelled: 31.00\n"
+        );
+        assert_eq!(
+            explain2(&input).unwrap().to_string(),
+            "horizontal position: 15\ndepth: 60\nmaximum depth: 60\ndistance travelled: 65.89\n"
+        );
     }
 }
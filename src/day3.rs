@@ -73,11 +73,11 @@
 //! The bit criteria depends on which type of rating value you want to find:
 //!
 //! -   To find oxygen generator rating, determine the most common value (`0` or `1`) in the
-//! current bit position, and keep only numbers with that bit in that position. If `0` and `1`
-//! are equally common, keep values with a `1` in the position being considered.
+//!     current bit position, and keep only numbers with that bit in that position. If `0` and `1`
+//!     are equally common, keep values with a `1` in the position being considered.
 //! -   To find CO2 scrubber rating, determine the least common value (`0` or `1`) in the current
-//! bit position, and keep only numbers with that bit in that position. If `0` and `1` are equally
-//! common, keep values with a `0` in the position being considered.
+//!     bit position, and keep only numbers with that bit in that position. If `0` and `1` are equally
+//!     common, keep values with a `0` in the position being considered.
 //!
 //! For example, to determine the oxygen generator rating value using the same example
 //! diagnostic report from above:
@@ -122,14 +122,65 @@
 //!
 //! (Be sure to represent your answer in decimal, not binary.)
 
-use bitlab::*;
+use crate::answer::Answer;
+use crate::bits::Bits;
+use crate::explain::Explanation;
 
+/// The diagnostic report: numbers of equal width, bit 0 is the most significant one
+#[derive(Debug, Clone)]
+pub(crate) struct Report {
+    width: usize,
+    /// sorted, so numbers sharing their leading bits are next to each other
+    numbers: Vec<u128>,
+}
+
+/// widest numbers a report may contain
+const MAX_WIDTH: usize = 128;
+
+/// The width of the report is the length of its lines, leading zeros included, so all lines
+/// need to be equally long.
 #[aoc_generator(day3)]
-fn parse_input(input: &str) -> Vec<u32> {
-    input
+pub(crate) fn parse_input(input: &str) -> anyhow::Result<Report> {
+    let width = input.lines().next().map(str::len).unwrap_or_default();
+    if width > MAX_WIDTH {
+        bail!(
+            "numbers of {} bits do not fit into {} bits",
+            width,
+            MAX_WIDTH
+        );
+    }
+    let mut numbers = input
         .lines()
-        .map(|line| u32::from_str_radix(line, 2).unwrap())
-        .collect()
+        .enumerate()
+        .map(|(idx, line)| {
+            let bits = Bits::from_binary(line)?;
+            if bits.len() != width {
+                bail!(
+                    "line {} has {} bits instead of {}",
+                    idx + 1,
+                    bits.len(),
+                    width
+                );
+            }
+            // the reader returns at most 64 bits at once
+            let mut reader = bits.reader();
+            let mut value = 0u128;
+            while !reader.is_empty() {
+                let len = reader.remaining().min(64);
+                value = value << len | reader.read(len)? as u128;
+            }
+            Ok(value)
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    numbers.sort_unstable();
+    Ok(Report { width, numbers })
+}
+
+impl Report {
+    /// bit at position `pos` of value, counted from the most significant bit
+    fn bit(&self, value: u128, pos: usize) -> bool {
+        value >> (self.width - 1 - pos) & 1 == 1
+    }
 }
 
 /// Part 1:
@@ -137,8 +188,8 @@ fn parse_input(input: &str) -> Vec<u32> {
 /// then multiply them together.
 /// What is the power consumption of the submarine?
 #[aoc(day3, part1)]
-fn part1(input: &[u32]) -> u64 {
-    gamma(input) as u64 * epsilon(input) as u64
+pub(crate) fn part1(input: &Report) -> Answer {
+    product(gamma(input), epsilon(input))
 }
 
 /// Part 2:
@@ -146,113 +197,282 @@ fn part1(input: &[u32]) -> u64 {
 /// Use the binary numbers in your diagnostic report to calculate the oxygen generator rating and
 /// CO2 scrubber rating, then multiply them together.
 #[aoc(day3, part2)]
-fn part2(input: &[u32]) -> u64 {
-    oxygen(input) as u64 * co2(input) as u64
+pub(crate) fn part2(input: &Report) -> Answer {
+    product(oxygen(input), co2(input))
 }
 
-///  To find oxygen generator rating, determine the most common value (`0` or `1`) in the
-///  current bit position, and keep only numbers with that bit in that position. If `0` and `1`
-///  are equally common, keep values with a `1` in the position being considered.
-fn oxygen(input: &[u32]) -> u32 {
-    let max = *input.iter().max().unwrap();
-    let start = significant_bitcount(max).unwrap();
-
-    let mut remaining = Vec::from(input);
-    for i in start..32 {
-        let (count_zeros, count_ones) = count_ones_zeros_at(&remaining, i);
-        if count_ones >= count_zeros {
-            remaining = remaining
-                .into_iter()
-                .filter(|v| v.get_bit(i).unwrap())
-                .collect();
-        } else {
-            remaining = remaining
-                .into_iter()
-                .filter(|v| !v.get_bit(i).unwrap())
-                .collect();
-        }
-        if remaining.len() == 1 {
-            return remaining[0];
+/// bit columns and the gamma and epsilon rate behind the power consumption of part 1
+pub(crate) fn explain1(input: &Report) -> Explanation {
+    let (gamma, epsilon) = (gamma(input), epsilon(input));
+    let statistics = Statistics::of(input);
+    let rows = statistics
+        .columns
+        .iter()
+        .enumerate()
+        .map(|(pos, column)| {
+            vec![
+                (pos + 1).to_string(),
+                column.zeros.to_string(),
+                column.ones.to_string(),
+                format!("{:.3}", column.entropy()),
+            ]
+        })
+        .collect();
+    let ties: Vec<String> = statistics
+        .ties()
+        .iter()
+        .map(|pos| (pos + 1).to_string())
+        .collect();
+    Explanation::new()
+        .table("columns", &["bit", "zeros", "ones", "entropy"], rows)
+        .value(
+            "tied bits",
+            match ties.is_empty() {
+                true => "none".to_string(),
+                false => ties.join(", "),
+            },
+        )
+        .value("gamma rate", binary(input, gamma))
+        .value("epsilon rate", binary(input, epsilon))
+        .value("power consumption", product(gamma, epsilon))
+}
+
+/// filter steps and ratings behind the life support rating of part 2
+pub(crate) fn explain2(input: &Report) -> Explanation {
+    let (oxygen, co2) = (oxygen(input), co2(input));
+    let statistics = Statistics::of(input);
+    let rows = |trace: &[FilterStep]| {
+        trace
+            .iter()
+            .map(|step| {
+                vec![
+                    (step.pos + 1).to_string(),
+                    step.zeros.to_string(),
+                    step.ones.to_string(),
+                    match step.kept {
+                        Some(bit) => (bit as u8).to_string(),
+                        None => "all".to_string(),
+                    },
+                    match step.is_tie() {
+                        true => "yes".to_string(),
+                        false => String::new(),
+                    },
+                    step.remaining.to_string(),
+                ]
+            })
+            .collect()
+    };
+    let header = ["bit", "zeros", "ones", "kept", "tie", "remaining"];
+    Explanation::new()
+        .table("oxygen generator filter", &header, rows(&statistics.oxygen))
+        .value("oxygen generator rating", binary(input, oxygen))
+        .table("CO2 scrubber filter", &header, rows(&statistics.co2))
+        .value("CO2 scrubber rating", binary(input, co2))
+        .value("life support rating", product(oxygen, co2))
+}
+
+/// Statistics of the bit columns of a report and of the filters finding its ratings
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Statistics {
+    /// counts of all numbers, one column per bit
+    pub columns: Vec<Column>,
+    /// steps of the oxygen generator rating, until a single number remains
+    pub oxygen: Vec<FilterStep>,
+    /// steps of the CO2 scrubber rating, until a single number remains
+    pub co2: Vec<FilterStep>,
+}
+
+/// Counts of zeros and ones at a bit position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Column {
+    pub zeros: u32,
+    pub ones: u32,
+}
+
+/// A bit position the ratings filter on, with counts of the numbers remaining before it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct FilterStep {
+    pub pos: usize,
+    pub zeros: u32,
+    pub ones: u32,
+    /// bit of the numbers kept, `None` if all numbers agree and are kept
+    pub kept: Option<bool>,
+    /// numbers remaining after this step
+    pub remaining: usize,
+}
+
+impl Statistics {
+    pub fn of(input: &Report) -> Statistics {
+        let columns = (0..input.width)
+            .map(|pos| {
+                let (zeros, ones) = count_ones_zeros_at(input, &input.numbers, pos);
+                Column { zeros, ones }
+            })
+            .collect();
+        let trace = |keep_ones: fn(u32, u32) -> bool| {
+            let mut steps = Vec::new();
+            filter(input, keep_ones, &mut |step| steps.push(step));
+            steps
+        };
+        Statistics {
+            columns,
+            oxygen: trace(most_common),
+            co2: trace(least_common),
         }
     }
-    0
+
+    /// positions of the columns with as many zeros as ones, where gamma takes a `1`
+    pub fn ties(&self) -> Vec<usize> {
+        (0..self.columns.len())
+            .filter(|&pos| self.columns[pos].is_tie())
+            .collect()
+    }
 }
 
-/// To find CO2 scrubber rating, determine the least common value (`0` or `1`) in the current
-/// bit position, and keep only numbers with that bit in that position. If `0` and `1` are equally
-/// common, keep values with a `0` in the position being considered.
-fn co2(input: &[u32]) -> u32 {
-    let max = *input.iter().max().unwrap();
-    let start = significant_bitcount(max).unwrap();
-
-    let mut remaining = Vec::from(input);
-    for i in start..32 {
-        let (count_zeros, count_ones) = count_ones_zeros_at(&remaining, i);
-        if count_zeros <= count_ones {
-            remaining = remaining
-                .into_iter()
-                .filter(|v| !v.get_bit(i).unwrap())
-                .collect();
-        } else {
-            remaining = remaining
-                .into_iter()
-                .filter(|v| v.get_bit(i).unwrap())
-                .collect();
-        }
-        if remaining.len() == 1 {
-            return remaining[0];
-        }
+impl Column {
+    pub fn is_tie(&self) -> bool {
+        self.zeros == self.ones
+    }
+
+    /// Shannon entropy in bits, 0 if all numbers agree and 1 for a tie
+    pub fn entropy(&self) -> f64 {
+        let total = (self.zeros + self.ones) as f64;
+        [self.zeros, self.ones]
+            .iter()
+            .filter(|&&count| count > 0)
+            .map(|&count| {
+                let p = count as f64 / total;
+                -p * p.log2()
+            })
+            .sum()
     }
-    0
 }
 
-fn count_ones_zeros_at(input: &[u32], pos: u32) -> (u32, u32) {
-    let mut count_zeros = 0;
-    let mut count_ones = 0;
-    for v in input {
-        if v.get_bit(pos).unwrap() {
-            count_ones += 1;
-        } else {
-            count_zeros += 1;
-        }
+impl FilterStep {
+    /// whether the tie-break rule of the rating chose the kept bit
+    pub fn is_tie(&self) -> bool {
+        self.zeros == self.ones
     }
-    (count_zeros, count_ones)
 }
 
-/// The gamma rate is calculated by using the least common bit from each position.
-fn gamma(input: &[u32]) -> u32 {
-    let mut ret = 0;
-    for i in 0..32 {
-        let (count_zeros, count_ones) = count_ones_zeros_at(input, i);
-        if count_ones >= count_zeros {
-            ret = ret.set_bit(i).unwrap();
+/// formats value in decimal and in binary, padded to the width of the report
+fn binary(input: &Report, value: u128) -> String {
+    format!("{} ({:0width$b})", value, value, width = input.width)
+}
+
+/// product of two rates or ratings, as text in decimal when it exceeds 128 bits
+fn product(a: u128, b: u128) -> Answer {
+    match a.checked_mul(b) {
+        Some(product) => product.into(),
+        None => Answer::Text(multiply_decimal(&a.to_string(), &b.to_string())),
+    }
+}
+
+/// long multiplication of two numbers given by their decimal digits
+fn multiply_decimal(a: &str, b: &str) -> String {
+    let mut digits = vec![0u32; a.len() + b.len()];
+    for (i, da) in a.bytes().rev().enumerate() {
+        let mut carry = 0;
+        for (j, db) in b.bytes().rev().enumerate() {
+            let sum = digits[i + j] + (da - b'0') as u32 * (db - b'0') as u32 + carry;
+            digits[i + j] = sum % 10;
+            carry = sum / 10;
         }
+        digits[i + b.len()] += carry;
+    }
+    let product: String = digits
+        .iter()
+        .rev()
+        .skip_while(|&&d| d == 0)
+        .map(|d| char::from(b'0' + *d as u8))
+        .collect();
+    match product.is_empty() {
+        true => "0".to_string(),
+        false => product,
     }
-    ret
 }
 
-fn significant_bitcount(value: u32) -> Option<u32> {
-    for i in 0..32 {
-        if value.get_bit(i).unwrap() {
-            return Some(i);
+///  To find oxygen generator rating, determine the most common value (`0` or `1`) in the
+///  current bit position, and keep only numbers with that bit in that position. If `0` and `1`
+///  are equally common, keep values with a `1` in the position being considered.
+fn oxygen(input: &Report) -> u128 {
+    filter(input, most_common, &mut |_| {})
+}
+
+/// To find CO2 scrubber rating, determine the least common value (`0` or `1`) in the current
+/// bit position, and keep only numbers with that bit in that position. If `0` and `1` are equally
+/// common, keep values with a `0` in the position being considered.
+fn co2(input: &Report) -> u128 {
+    filter(input, least_common, &mut |_| {})
+}
+
+fn most_common(count_zeros: u32, count_ones: u32) -> bool {
+    count_ones >= count_zeros
+}
+
+fn least_common(count_zeros: u32, count_ones: u32) -> bool {
+    count_zeros > count_ones
+}
+
+/// filters the numbers bit by bit, keeping those whose bit equals the one chosen from the
+/// counts of zeros and ones among the remaining numbers
+///
+/// The remaining numbers are a range of the sorted numbers which share all bits before the
+/// current position, so those with a `0` there come first and a binary search finds where the
+/// ones start. Positions where all remaining numbers agree, like leading zeros, keep all of
+/// them. Numbers left over after the last position are equal. Every step is passed to `visit`.
+fn filter<F, V>(input: &Report, keep_ones: F, visit: &mut V) -> u128
+where
+    F: Fn(u32, u32) -> bool,
+    V: FnMut(FilterStep),
+{
+    let (mut start, mut end) = (0, input.numbers.len());
+    for pos in 0..input.width {
+        if end - start <= 1 {
+            break;
         }
+        let ones = start + input.numbers[start..end].partition_point(|&v| !input.bit(v, pos));
+        let (count_zeros, count_ones) = ((ones - start) as u32, (end - ones) as u32);
+        let kept = match count_zeros == 0 || count_ones == 0 {
+            true => None,
+            false => Some(keep_ones(count_zeros, count_ones)),
+        };
+        match kept {
+            Some(true) => start = ones,
+            Some(false) => end = ones,
+            None => {}
+        }
+        visit(FilterStep {
+            pos,
+            zeros: count_zeros,
+            ones: count_ones,
+            kept,
+            remaining: end - start,
+        });
     }
-    None
+    input.numbers.get(start).copied().unwrap_or_default()
+}
+
+fn count_ones_zeros_at(input: &Report, numbers: &[u128], pos: usize) -> (u32, u32) {
+    let count_ones = numbers.iter().filter(|&&v| input.bit(v, pos)).count() as u32;
+    (numbers.len() as u32 - count_ones, count_ones)
+}
+
+/// The gamma rate is calculated by using the most common bit from each position.
+fn gamma(input: &Report) -> u128 {
+    (0..input.width).fold(0, |gamma, pos| {
+        let (count_zeros, count_ones) = count_ones_zeros_at(input, &input.numbers, pos);
+        gamma << 1 | (count_ones >= count_zeros) as u128
+    })
 }
 
 /// The epsilon rate is calculated in a similar way; rather than use the most common bit,
 /// the least common bit from each position is used.
-fn epsilon(input: &[u32]) -> u32 {
-    let gamma = gamma(input);
-    let max = *input.iter().max().unwrap();
-    let start = significant_bitcount(max).unwrap();
-    let mut ret = 0;
-    for i in start..32 {
-        if !gamma.get_bit(i).unwrap() {
-            ret = ret.set_bit(i).unwrap()
-        }
-    }
-    ret
+fn epsilon(input: &Report) -> u128 {
+    let mask = u128::MAX
+        .checked_shr((MAX_WIDTH - input.width) as u32)
+        .unwrap_or_default();
+    !gamma(input) & mask
 }
 
 #[cfg(test)]
@@ -275,22 +495,181 @@ mod tests {
     #[test]
     fn part1_examples() {
         // So, the gamma rate is the binary number `10110`, or `22` in decimal.
-        assert_eq!(gamma(&parse_input(EXAMPLE)), 0b1_0110);
-        assert_eq!(gamma(&parse_input(EXAMPLE)), 22);
+        assert_eq!(gamma(&parse_input(EXAMPLE).unwrap()), 0b1_0110);
+        assert_eq!(gamma(&parse_input(EXAMPLE).unwrap()), 22);
 
         // So, the epsilon rate is `01001`, or `9` in decimal.
-        assert_eq!(epsilon(&parse_input(EXAMPLE)), 0b1001);
-        assert_eq!(epsilon(&parse_input(EXAMPLE)), 9);
+        assert_eq!(epsilon(&parse_input(EXAMPLE).unwrap()), 0b1001);
+        assert_eq!(epsilon(&parse_input(EXAMPLE).unwrap()), 9);
     }
 
     #[test]
     fn part2_examples() {
         // the oxygen generator rating is `10111`, or `23` in decimal.
-        assert_eq!(oxygen(&parse_input(EXAMPLE)), 0b1_0111);
-        assert_eq!(oxygen(&parse_input(EXAMPLE)), 23);
+        assert_eq!(oxygen(&parse_input(EXAMPLE).unwrap()), 0b1_0111);
+        assert_eq!(oxygen(&parse_input(EXAMPLE).unwrap()), 23);
 
         // the CO2 scrubber rating is `01010`, or `10` in decimal.
-        assert_eq!(co2(&parse_input(EXAMPLE)), 0b0_1010);
-        assert_eq!(co2(&parse_input(EXAMPLE)), 10);
+        assert_eq!(co2(&parse_input(EXAMPLE).unwrap()), 0b0_1010);
+        assert_eq!(co2(&parse_input(EXAMPLE).unwrap()), 10);
+    }
+
+    #[test]
+    fn leading_zeros() {
+        // the width comes from the lines even if no number uses the leading bits
+        let input = parse_input("0001\n0011\n0010\n0001").unwrap();
+        assert_eq!(gamma(&input), 0b0011);
+        assert_eq!(epsilon(&input), 0b1100);
+        assert_eq!(oxygen(&input), 0b0011);
+        assert_eq!(co2(&input), 0b0001);
+    }
+
+    #[test]
+    fn wide_reports() {
+        let input =
+            parse_input(&["1".repeat(128), "1".repeat(128), "0".repeat(128)].join("\n")).unwrap();
+        assert_eq!(gamma(&input), u128::MAX);
+        assert_eq!(epsilon(&input), 0);
+        assert_eq!(part1(&input), Answer::Int(0));
+
+        let input =
+            parse_input(&[format!("1{}", "0".repeat(69)), "0".repeat(70)].join("\n")).unwrap();
+        assert_eq!(gamma(&input), 1 << 69);
+        assert_eq!(epsilon(&input), (1 << 69) - 1);
+        assert_eq!(part2(&input), Answer::Int(0));
+
+        let input =
+            parse_input(&[format!("01{}", "1".repeat(126)), "1".repeat(128)].join("\n")).unwrap();
+        assert_eq!((oxygen(&input), co2(&input)), (u128::MAX, u128::MAX >> 1));
+        assert_eq!(
+            part2(&input),
+            Answer::Text(
+                "57896044618658097711785492504343953926124568782438874324533730092808912502785"
+                    .into()
+            )
+        );
+    }
+
+    /// the ratings as described, removing numbers from a list at every position
+    fn filtered_rating(input: &Report, keep_ones: fn(u32, u32) -> bool) -> u128 {
+        let mut remaining = input.numbers.clone();
+        for pos in 0..input.width {
+            let count_ones = remaining.iter().filter(|&&v| input.bit(v, pos)).count() as u32;
+            let count_zeros = remaining.len() as u32 - count_ones;
+            if remaining.len() > 1 && count_zeros > 0 && count_ones > 0 {
+                let bit = keep_ones(count_zeros, count_ones);
+                remaining.retain(|&v| input.bit(v, pos) == bit);
+            }
+        }
+        remaining[0]
+    }
+
+    #[test]
+    fn ratings_match_filtering() {
+        for seed in 1..50u32 {
+            let lines: Vec<String> = (0..seed * 3)
+                .map(|i| {
+                    format!(
+                        "{:010b}",
+                        i.wrapping_mul(2_654_435_761).wrapping_add(seed) >> 22
+                    )
+                })
+                .collect();
+            let input = parse_input(&lines.join("\n")).unwrap();
+            assert_eq!(oxygen(&input), filtered_rating(&input, |z, o| o >= z));
+            assert_eq!(co2(&input), filtered_rating(&input, |z, o| z > o));
+        }
+    }
+
+    #[test]
+    fn parse_errors() {
+        let error = |input| parse_input(input).unwrap_err().to_string();
+        assert_eq!(error("0101\n011"), "line 2 has 3 bits instead of 4");
+        assert_eq!(error("0121"), "invalid binary digit `2` at position 2");
+        assert_eq!(
+            error(&"1".repeat(129)),
+            "numbers of 129 bits do not fit into 128 bits"
+        );
+    }
+
+    #[test]
+    fn explain_examples() {
+        let input = parse_input(EXAMPLE).unwrap();
+        assert_eq!(
+            explain1(&input).to_string(),
+            "columns:
+  bit | zeros | ones | entropy
+    1 |     5 |    7 |   0.980
+    2 |     7 |    5 |   0.980
+    3 |     4 |    8 |   0.918
+    4 |     5 |    7 |   0.980
+    5 |     7 |    5 |   0.980
+tied bits: none
+gamma rate: 22 (10110)
+epsilon rate: 9 (01001)
+power consumption: 198
+"
+        );
+        assert_eq!(
+            explain2(&input).to_string(),
+            "oxygen generator filter:
+  bit | zeros | ones | kept | tie | remaining
+    1 |     5 |    7 |    1 |     |         7
+    2 |     4 |    3 |    0 |     |         4
+    3 |     1 |    3 |    1 |     |         3
+    4 |     1 |    2 |    1 |     |         2
+    5 |     1 |    1 |    1 | yes |         1
+oxygen generator rating: 23 (10111)
+CO2 scrubber filter:
+  bit | zeros | ones | kept | tie | remaining
+    1 |     5 |    7 |    0 |     |         5
+    2 |     3 |    2 |    1 |     |         2
+    3 |     1 |    1 |    0 | yes |         1
+CO2 scrubber rating: 10 (01010)
+life support rating: 230
+"
+        );
+    }
+
+    #[test]
+    fn statistics() {
+        let input = parse_input("0001\n0011\n0010\n0001").unwrap();
+        let statistics = Statistics::of(&input);
+        assert_eq!(statistics.ties(), [2]);
+        assert_eq!(statistics.columns[0].entropy(), 0.0);
+        assert_eq!(statistics.columns[2].entropy(), 1.0);
+        assert_eq!(
+            statistics.co2,
+            [
+                FilterStep {
+                    pos: 0,
+                    zeros: 4,
+                    ones: 0,
+                    kept: None,
+                    remaining: 4
+                },
+                FilterStep {
+                    pos: 1,
+                    zeros: 4,
+                    ones: 0,
+                    kept: None,
+                    remaining: 4
+                },
+                FilterStep {
+                    pos: 2,
+                    zeros: 2,
+                    ones: 2,
+                    kept: Some(false),
+                    remaining: 2
+                },
+                FilterStep {
+                    pos: 3,
+                    zeros: 0,
+                    ones: 2,
+                    kept: None,
+                    remaining: 2
+                },
+            ]
+        );
     }
 }
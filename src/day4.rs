@@ -96,72 +96,271 @@
 //!
 //! **Figure out which board will win last. Once it wins, what would its final score be?**
 
+use crate::answer::Answer;
+use crate::explain::Explanation;
+use crate::validate::Violation;
+use std::fmt::{Display, Formatter};
+
+/// The first non-blank line holds the drawn numbers, followed by square boards of any size
+/// which are separated by blank lines. Numbers may be separated by any amount of whitespace.
 #[aoc_generator(day4)]
-fn parse_input(input: &str) -> BingoGame {
+pub(crate) fn parse_input(input: &str) -> anyhow::Result<BingoGame> {
+    let mut lines = input
+        .lines()
+        .enumerate()
+        .skip_while(|(_, l)| l.trim().is_empty());
+    let numbers = match lines.next() {
+        Some((_, line)) => line
+            .split(',')
+            .map(|n| n.trim().parse())
+            .collect::<Result<_, _>>()?,
+        None => bail!("no drawn numbers"),
+    };
     let mut boards = Vec::new();
-    let mut numbers = Vec::new();
-    let mut buffer = String::new();
-    const HEADER_LINES: usize = 2;
-    const FOOTER_LINES: usize = 1;
-    for (nr, line) in input.lines().enumerate() {
-        if nr == 0 {
-            numbers = line.split(',').map(|c| c.parse().unwrap()).collect();
-        } else if nr >= HEADER_LINES {
-            buffer += &*line.replace("  ", " ").trim();
-            buffer += "\n";
-            if (nr - HEADER_LINES) % (BOARD_SIZE + FOOTER_LINES) == BOARD_SIZE {
-                boards.push(BingoBoard::new(&buffer));
-                buffer = String::new();
+    let mut rows: Vec<Vec<u32>> = Vec::new();
+    let mut start = 0;
+    // a blank line after the input ends the last board
+    for (idx, line) in lines.chain(std::iter::once((0, ""))) {
+        if !line.trim().is_empty() {
+            if rows.is_empty() {
+                start = idx + 1;
             }
+            let row = line
+                .split_whitespace()
+                .map(|n| n.parse())
+                .collect::<Result<_, _>>()?;
+            rows.push(row);
+            continue;
+        }
+        if rows.is_empty() {
+            continue;
         }
+        let board = BingoBoard::new(std::mem::take(&mut rows))?;
+        let size = boards.first().map_or(board.size(), BingoBoard::size);
+        if board.size() != size {
+            bail!(
+                "board starting on line {} has {} rows instead of {}",
+                start,
+                board.size(),
+                size
+            );
+        }
+        boards.push(board);
     }
-    boards.push(BingoBoard::new(&buffer));
-
-    BingoGame { boards, numbers }
+    if boards.is_empty() {
+        bail!("no boards");
+    }
+    Ok(BingoGame::with_rules(numbers, boards, Rules::default()))
 }
 
 /// Part 1: Figure out which board will win first. What will your final score be if you choose that board?
 #[aoc(day4, part1)]
-fn part1(game: &BingoGame) -> u64 {
-    game.play_win_first().expect("no winner")
+pub(crate) fn part1(game: &BingoGame) -> anyhow::Result<Answer> {
+    match game.play().first() {
+        Some(win) => Ok(win.score().into()),
+        None => bail!("no board wins"),
+    }
 }
 
 /// Part 2: Figure out which board will win last. Once it wins, what would its final score be?
 #[aoc(day4, part2)]
-fn part2(game: &BingoGame) -> u64 {
-    game.play_win_last().expect("no winner")
+pub(crate) fn part2(game: &BingoGame) -> anyhow::Result<Answer> {
+    match game.play().last() {
+        Some(win) => Ok(win.score().into()),
+        None => bail!("no board wins"),
+    }
 }
 
-const BOARD_SIZE: usize = 5;
+/// board winning first and the draw it won on
+pub(crate) fn explain1(game: &BingoGame) -> Explanation {
+    game.play().first().map(Win::explain).unwrap_or_default()
+}
 
-#[derive(Clone)]
-struct BingoBoard {
-    values: [[u8; BOARD_SIZE]; BOARD_SIZE],
-    marked: [[bool; BOARD_SIZE]; BOARD_SIZE],
+/// order in which the boards win, then the board winning last and the draw it won on
+pub(crate) fn explain2(game: &BingoGame) -> Explanation {
+    let timeline = game.play();
+    let rows = timeline
+        .wins
+        .iter()
+        .map(|win| {
+            vec![
+                (win.board + 1).to_string(),
+                format!("#{} ({})", win.draw + 1, win.number),
+                win.pattern.to_string(),
+                win.score().to_string(),
+            ]
+        })
+        .chain(timeline.never_won.iter().map(|board| {
+            vec![
+                (board + 1).to_string(),
+                "never".to_string(),
+                String::new(),
+                String::new(),
+            ]
+        }))
+        .collect();
+    let last = timeline.last().map(Win::explain).unwrap_or_default();
+    let mut explanation = Explanation::new().table(
+        "finishing order",
+        &["board", "draw", "pattern", "score"],
+        rows,
+    );
+    explanation.entries.extend(last.entries);
+    explanation
 }
 
-impl BingoBoard {
-    fn new(board: &str) -> BingoBoard {
-        let mut values = [[0; BOARD_SIZE]; BOARD_SIZE];
-        let marked = [[false; BOARD_SIZE]; BOARD_SIZE];
-        for (y, line) in board.lines().enumerate() {
-            if line.is_empty() {
-                break;
+/// Checks what `parse_input` relies on: a line of drawn numbers followed by square boards of
+/// the same size, which is taken from the number of rows of the first board.
+pub(crate) fn validate(input: &str) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    let mut lines = input
+        .lines()
+        .enumerate()
+        .skip_while(|(_, l)| l.trim().is_empty());
+    let (first, numbers) = lines.next().unwrap_or_default();
+    let mut column = 1;
+    for number in numbers.split(',') {
+        if number.trim().parse::<u32>().is_err() {
+            violations.push(Violation::at(
+                first + 1,
+                column,
+                format!("invalid number `{}`", number.trim()),
+            ));
+        }
+        column += number.len() + 1;
+    }
+
+    // boards as line numbers and numbers of their rows
+    let mut boards: Vec<Vec<(usize, Vec<&str>)>> = Vec::new();
+    let mut rows = Vec::new();
+    for (idx, line) in lines {
+        if line.trim().is_empty() {
+            if !rows.is_empty() {
+                boards.push(std::mem::take(&mut rows));
+            }
+            continue;
+        }
+        let nr = idx + 1;
+        let mut column = 1;
+        for word in line.split_inclusive(char::is_whitespace) {
+            let value = word.trim_end();
+            if !value.is_empty() && value.parse::<u32>().is_err() {
+                violations.push(Violation::at(
+                    nr,
+                    column,
+                    format!("invalid number `{}`", value),
+                ));
             }
-            for (x, value) in line.split(' ').enumerate() {
-                values[y][x] = value.parse().unwrap();
+            column += word.len();
+        }
+        rows.push((nr, line.split_whitespace().collect()));
+    }
+    if !rows.is_empty() {
+        boards.push(rows);
+    }
+    let size = boards.first().map(Vec::len).unwrap_or_default();
+    for board in &boards {
+        for (nr, values) in board {
+            if values.len() != size {
+                violations.push(Violation::at_line(
+                    *nr,
+                    format!("row has {} numbers, expected {}", values.len(), size),
+                ));
             }
         }
-        BingoBoard { values, marked }
+        if board.len() != size {
+            violations.push(Violation::at_line(
+                board[0].0,
+                format!("board has {} rows, expected {}", board.len(), size),
+            ));
+        }
+    }
+    if boards.is_empty() {
+        violations.push(Violation::general("no boards found"));
+    }
+    violations.sort_by_key(|v| v.line);
+    violations
+}
+
+/// Which patterns of marked numbers win
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Rules {
+    /// complete rows and columns
+    pub lines: bool,
+    /// both diagonals through the board
+    pub diagonals: bool,
+    /// the four corners of the board
+    pub four_corners: bool,
+    /// all numbers of the board
+    pub full_card: bool,
+}
+
+impl Default for Rules {
+    /// only rows and columns win, as in the puzzle
+    fn default() -> Rules {
+        Rules {
+            lines: true,
+            diagonals: false,
+            four_corners: false,
+            full_card: false,
+        }
     }
+}
 
-    fn play(&mut self, n: u8) -> Option<u64> {
-        for x in 0..BOARD_SIZE {
-            for y in 0..BOARD_SIZE {
+/// A winning pattern of marked numbers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Pattern {
+    Row(usize),
+    Column(usize),
+    /// from the top left to the bottom right corner
+    Diagonal,
+    /// from the top right to the bottom left corner
+    AntiDiagonal,
+    FourCorners,
+    FullCard,
+}
+
+impl Display for Pattern {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Pattern::Row(y) => write!(f, "row {}", y + 1),
+            Pattern::Column(x) => write!(f, "column {}", x + 1),
+            Pattern::Diagonal => write!(f, "diagonal"),
+            Pattern::AntiDiagonal => write!(f, "anti-diagonal"),
+            Pattern::FourCorners => write!(f, "four corners"),
+            Pattern::FullCard => write!(f, "full card"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct BingoBoard {
+    values: Vec<Vec<u32>>,
+    marked: Vec<Vec<bool>>,
+}
+
+impl BingoBoard {
+    fn new(values: Vec<Vec<u32>>) -> anyhow::Result<BingoBoard> {
+        let size = values.len();
+        if let Some(row) = values.iter().find(|row| row.len() != size) {
+            bail!("board of {} rows has a row of {} numbers", size, row.len());
+        }
+        let marked = vec![vec![false; size]; size];
+        Ok(BingoBoard { values, marked })
+    }
+
+    fn size(&self) -> usize {
+        self.values.len()
+    }
+
+    /// marks the number, returning the pattern it completed if the board wins with it
+    fn play(&mut self, n: u32, rules: &Rules) -> Option<Pattern> {
+        for y in 0..self.size() {
+            for x in 0..self.size() {
                 if self.values[y][x] == n {
                     self.marked[y][x] = true;
-                    if self.has_win() {
-                        return Some(self.score());
+                    if let Some(pattern) = self.has_win(rules) {
+                        return Some(pattern);
                     }
                 }
             }
@@ -171,8 +370,8 @@ impl BingoBoard {
 
     fn score(&self) -> u64 {
         let mut score = 0;
-        for x in 0..BOARD_SIZE {
-            for y in 0..BOARD_SIZE {
+        for y in 0..self.size() {
+            for x in 0..self.size() {
                 if !self.marked[y][x] {
                     score += self.values[y][x] as u64
                 }
@@ -181,62 +380,149 @@ impl BingoBoard {
         score
     }
 
-    fn has_win(&self) -> bool {
-        for a in 0..BOARD_SIZE {
-            let mut count_a = 0;
-            let mut count_b = 0;
-            for b in 0..BOARD_SIZE {
-                if self.marked[a][b] {
-                    count_a += 1;
-                }
-                if self.marked[b][a] {
-                    count_b += 1;
-                }
+    /// first complete pattern allowed by the rules, rows and columns before the others
+    fn has_win(&self, rules: &Rules) -> Option<Pattern> {
+        let size = self.size();
+        for a in (0..size).filter(|_| rules.lines) {
+            if (0..size).all(|b| self.marked[a][b]) {
+                return Some(Pattern::Row(a));
+            }
+            if (0..size).all(|b| self.marked[b][a]) {
+                return Some(Pattern::Column(a));
+            }
+        }
+        let last = size - 1;
+        if rules.diagonals {
+            if (0..size).all(|i| self.marked[i][i]) {
+                return Some(Pattern::Diagonal);
             }
-            if count_a == BOARD_SIZE || count_b == BOARD_SIZE {
-                return true;
+            if (0..size).all(|i| self.marked[i][last - i]) {
+                return Some(Pattern::AntiDiagonal);
             }
         }
-        false
+        if rules.four_corners
+            && [(0, 0), (0, last), (last, 0), (last, last)]
+                .iter()
+                .all(|&(y, x)| self.marked[y][x])
+        {
+            return Some(Pattern::FourCorners);
+        }
+        if rules.full_card && self.marked.iter().flatten().all(|&m| m) {
+            return Some(Pattern::FullCard);
+        }
+        None
     }
 }
 
-struct BingoGame {
-    numbers: Vec<u8>,
+/// A board completing a winning pattern
+#[derive(Debug, Clone)]
+pub(crate) struct Win {
+    /// index of the board in the input
+    pub board: usize,
+    /// index of the winning draw
+    pub draw: usize,
+    pub number: u32,
+    pub pattern: Pattern,
+    /// board right after winning
+    state: BingoBoard,
+}
+
+impl Win {
+    /// winning number times the sum of unmarked numbers, which can exceed 64 bits
+    pub fn score(&self) -> u128 {
+        u128::from(self.number) * u128::from(self.state.score())
+    }
+
+    /// winning board and draw, marked numbers of the board in brackets
+    fn explain(&self) -> Explanation {
+        let size = self.state.size();
+        let rows = (0..size)
+            .map(|y| {
+                (0..size)
+                    .map(|x| match self.state.marked[y][x] {
+                        true => format!("[{}]", self.state.values[y][x]),
+                        false => self.state.values[y][x].to_string(),
+                    })
+                    .collect()
+            })
+            .collect();
+        let header: Vec<String> = (1..=size).map(|x| x.to_string()).collect();
+        let header: Vec<&str> = header.iter().map(String::as_str).collect();
+        Explanation::new()
+            .value("board", self.board + 1)
+            .value("draw", format!("#{} ({})", self.draw + 1, self.number))
+            .value("pattern", self.pattern)
+            .value("sum of unmarked numbers", self.state.score())
+            .table("marked board", &header, rows)
+            .value("final score", self.score())
+    }
+}
+
+/// Outcome of a whole game
+#[derive(Debug, Clone)]
+pub(crate) struct Timeline {
+    /// every board that wins, in the order they win, boards winning on the same draw in the
+    /// order of the input
+    pub wins: Vec<Win>,
+    /// indices of the boards still not winning after the last draw
+    pub never_won: Vec<usize>,
+}
+
+impl Timeline {
+    pub fn first(&self) -> Option<&Win> {
+        self.wins.first()
+    }
+
+    pub fn last(&self) -> Option<&Win> {
+        self.wins.last()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct BingoGame {
+    numbers: Vec<u32>,
     boards: Vec<BingoBoard>,
+    rules: Rules,
 }
 
 impl BingoGame {
-    fn play_win_first(&self) -> Option<u64> {
-        let mut boards = self.boards.clone();
-        for n in &self.numbers {
-            for b in boards.iter_mut() {
-                if let Some(score) = b.play(*n) {
-                    return Some(*n as u64 * score);
-                }
-            }
+    /// a game of the drawn numbers on the boards, won by the patterns the rules allow
+    fn with_rules(numbers: Vec<u32>, boards: Vec<BingoBoard>, rules: Rules) -> BingoGame {
+        BingoGame {
+            numbers,
+            boards,
+            rules,
         }
-        None
     }
-    fn play_win_last(&self) -> Option<u64> {
+
+    /// draws the numbers until every board has won or all numbers are drawn, boards stop
+    /// playing once they won
+    pub fn play(&self) -> Timeline {
         let mut boards = self.boards.clone();
-        for n in &self.numbers {
-            let mut idx_to_remove: Vec<usize> = Vec::new();
-            let count = boards.len();
-            for (idx, b) in boards.iter_mut().enumerate() {
-                if let Some(score) = b.play(*n) {
-                    if count == 1 {
-                        return Some(*n as u64 * score);
-                    } else {
-                        idx_to_remove.push(idx);
-                    }
-                }
+        let mut won = vec![false; boards.len()];
+        let mut wins = Vec::new();
+        for (draw, n) in self.numbers.iter().enumerate() {
+            if wins.len() == boards.len() {
+                break;
             }
-            for (offset, idx) in idx_to_remove.into_iter().enumerate() {
-                boards.remove(idx - offset);
+            for (board, b) in boards.iter_mut().enumerate() {
+                if won[board] {
+                    continue;
+                }
+                if let Some(pattern) = b.play(*n, &self.rules) {
+                    won[board] = true;
+                    wins.push(Win {
+                        board,
+                        draw,
+                        number: *n,
+                        pattern,
+                        state: b.clone(),
+                    });
+                }
             }
         }
-        None
+        let never_won = (0..boards.len()).filter(|&board| !won[board]).collect();
+        Timeline { wins, never_won }
     }
 }
 
@@ -262,15 +548,43 @@ mod tests {
 10 16 15  9 19
 18  8 23 26 20
 22 11 13  6  5
- 2  0 12  3";
+ 2  0 12  3  7";
+
+    #[test]
+    fn validate_examples() {
+        assert_eq!(validate(EXAMPLE), vec![]);
+        let broken = EXAMPLE
+            .replacen("7,4,9", "7,x,9", 1)
+            .replacen(" 8  2 23  4 24\n", " 8  2 23  4\n", 1)
+            .replacen("\n 2  0 12  3  7", "", 1);
+        assert_eq!(
+            validate(&broken),
+            vec![
+                Violation::at(1, 3, "invalid number `x`"),
+                Violation::at_line(4, "row has 4 numbers, expected 5"),
+                Violation::at_line(15, "board has 4 rows, expected 5"),
+            ]
+        );
+        // the column of the word itself, not of the first text equal to it
+        assert_eq!(
+            validate("1\n\nx1 x\n1 2"),
+            vec![
+                Violation::at(3, 1, "invalid number `x1`"),
+                Violation::at(3, 4, "invalid number `x`"),
+            ]
+        );
+    }
 
     #[test]
     fn part1_examples() {
         // The score of the winning board can now be calculated. Start by finding the sum of all unmarked
         // numbers on that board; in this case, the sum is `188`. Then, multiply that sum by the number
         // that was just called when the board won, `24`, to get the final score, `188 * 24 = 4512`.
-        let result = parse_input(EXAMPLE).play_win_first().expect("no winner");
-        assert_eq!(result, 4512);
+        let timeline = parse_input(EXAMPLE).unwrap().play();
+        let result = timeline.first().expect("no winner");
+        assert_eq!((result.board, result.number), (2, 24));
+        assert_eq!(result.state.score(), 188);
+        assert_eq!(result.score(), 4512);
     }
 
     #[test]
@@ -279,7 +593,157 @@ mod tests {
         // eventually called and its middle column is completely marked. If you were to keep playing
         // until this point, the second board would have a sum of unmarked numbers equal to `148` for
         // a final score of `148 * 13 = 1924`.
-        let result = parse_input(EXAMPLE).play_win_last().expect("no winner");
-        assert_eq!(result, 1924);
+        let timeline = parse_input(EXAMPLE).unwrap().play();
+        let result = timeline.last().expect("no winner");
+        assert_eq!((result.board, result.number), (1, 13));
+        assert_eq!(result.state.score(), 148);
+        assert_eq!(result.score(), 1924);
+    }
+
+    #[test]
+    fn timeline() {
+        let timeline = parse_input(EXAMPLE).unwrap().play();
+        let finishes: Vec<_> = timeline
+            .wins
+            .iter()
+            .map(|win| (win.board, win.draw, win.number, win.pattern, win.score()))
+            .collect();
+        assert_eq!(
+            finishes,
+            [
+                (2, 11, 24, Pattern::Row(0), 4512),
+                (0, 13, 16, Pattern::Row(2), 2192),
+                (1, 14, 13, Pattern::Column(2), 1924),
+            ]
+        );
+        assert!(timeline.never_won.is_empty());
+
+        // boards winning on the same draw, and a board without any drawn number
+        let timeline = parse_input("1,2\n\n1 2\n3 4\n\n2 1\n4 3\n\n5 6\n7 8")
+            .unwrap()
+            .play();
+        let finishes: Vec<_> = timeline.wins.iter().map(|w| (w.board, w.draw)).collect();
+        assert_eq!(finishes, [(0, 1), (1, 1)]);
+        assert_eq!(timeline.never_won, [2]);
+    }
+
+    #[test]
+    fn rules() {
+        let game = parse_input("1,3,7,9,5,2,4,6,8\n\n1 2 3\n4 5 6\n7 8 9").unwrap();
+        let win = |rules| {
+            let game = BingoGame::with_rules(game.numbers.clone(), game.boards.clone(), rules);
+            game.play().wins.into_iter().next()
+        };
+        assert_eq!(win(Rules::default()).unwrap().pattern, Pattern::Row(0));
+        let diagonals = Rules {
+            diagonals: true,
+            ..Rules::default()
+        };
+        assert_eq!(win(diagonals).unwrap().pattern, Pattern::Diagonal);
+        let corners = Rules {
+            four_corners: true,
+            ..Rules::default()
+        };
+        assert_eq!(win(corners).unwrap().pattern, Pattern::FourCorners);
+        assert_eq!(win(corners).unwrap().score(), 9 * (2 + 4 + 5 + 6 + 8));
+        let full_card = Rules {
+            lines: false,
+            full_card: true,
+            ..Rules::default()
+        };
+        let result = win(full_card).unwrap();
+        assert_eq!(
+            (result.pattern, result.draw, result.score()),
+            (Pattern::FullCard, 8, 0)
+        );
+        let nothing = Rules {
+            lines: false,
+            ..Rules::default()
+        };
+        assert!(win(nothing).is_none());
+    }
+
+    #[test]
+    fn any_size_and_spacing() {
+        let game = parse_input(
+            "
+  4, 2,6 , 3,5,1
+
+
+ 1 2
+ 3   4
+\t
+\t
+5\t6
+4  3  ",
+        )
+        .unwrap();
+        let timeline = game.play();
+        let first = timeline.first().unwrap();
+        assert_eq!(
+            (first.board, first.pattern, first.score()),
+            (0, Pattern::Column(1), 2 * (1 + 3))
+        );
+        let last = timeline.last().unwrap();
+        assert_eq!(
+            (last.board, last.pattern, last.score()),
+            (1, Pattern::Row(1), 3 * 5)
+        );
+        assert_eq!(validate("4,2\n\n1 2\n3 4\n\n\n4 3\n2 1"), vec![]);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let error = |input| parse_input(input).unwrap_err().to_string();
+        assert_eq!(
+            error("1,2\n\n1 2\n3"),
+            "board of 2 rows has a row of 1 numbers"
+        );
+        assert_eq!(
+            error("1,2\n\n1 2\n3 4\n\n1\n"),
+            "board starting on line 6 has 1 rows instead of 2"
+        );
+        assert_eq!(error("1,2\n\n"), "no boards");
+    }
+
+    #[test]
+    fn large_numbers() {
+        let game = parse_input("1,4294967295\n\n4294967295 1\n4294967294 4294967293").unwrap();
+        assert_eq!(
+            part1(&game).unwrap(),
+            Answer::BigInt(4294967295 * (4294967294 + 4294967293))
+        );
+    }
+
+    #[test]
+    fn explain_examples() {
+        assert_eq!(
+            explain1(&parse_input(EXAMPLE).unwrap()).to_string(),
+            "board: 3
+draw: #12 (24)
+pattern: row 1
+sum of unmarked numbers: 188
+marked board:
+     1 |    2 |    3 |    4 |   5
+  [14] | [21] | [17] | [24] | [4]
+    10 |   16 |   15 |  [9] |  19
+    18 |    8 | [23] |   26 |  20
+    22 | [11] |   13 |    6 | [5]
+   [2] |  [0] |   12 |    3 | [7]
+final score: 4512
+"
+        );
+        assert!(explain2(&parse_input(EXAMPLE).unwrap())
+            .to_string()
+            .starts_with(
+                "finishing order:
+  board |     draw |  pattern | score
+      3 | #12 (24) |    row 1 |  4512
+      1 | #14 (16) |    row 3 |  2192
+      2 | #15 (13) | column 3 |  1924
+board: 2
+draw: #15 (13)
+"
+            ));
     }
 }
@@ -89,31 +89,35 @@
 //!
 //! Consider all of the lines. **At how many points do at least two lines overlap?**
 
+use crate::answer::Answer;
 use grid::Grid;
 
 #[aoc_generator(day5)]
-fn parse_input(input: &str) -> Vec<Line> {
+pub(crate) fn parse_input(input: &str) -> anyhow::Result<Vec<Line>> {
     input.lines().map(Line::parse).collect()
 }
 
 /// Part 1: At how many points do at least two lines overlap?
 #[aoc(day5, part1)]
-fn part1(input: &[Line]) -> usize {
+pub(crate) fn part1(input: &[Line]) -> Answer {
     let non_diagonals: Vec<Line> = Vec::from(input)
         .into_iter()
         .filter(|line| !line.is_diagonal())
         .collect();
     let grid = build_grid(&non_diagonals);
-    grid.iter().filter(|v| **v >= 2).count()
+    grid.iter().filter(|v| **v >= 2).count().into()
 }
 
 /// Part 2: At how many points do at least two lines overlap?
 #[aoc(day5, part2)]
-fn part2(input: &[Line]) -> usize {
+pub(crate) fn part2(input: &[Line]) -> Answer {
     let grid = build_grid(input);
-    grid.iter().filter(|v| **v >= 2).count()
+    grid.iter().filter(|v| **v >= 2).count().into()
 }
 
+/// upper bound for both coordinates, real inputs stay below 1000
+const MAX_COORDINATE: usize = 1 << 12;
+
 #[derive(Debug, Clone)]
 struct Point {
     x: usize,
@@ -121,28 +125,42 @@ struct Point {
 }
 
 impl Point {
-    fn parse(input: &str) -> Point {
-        let parts: Vec<&str> = input.split(',').collect();
-        Point {
-            x: parts[0].parse().unwrap(),
-            y: parts[1].parse().unwrap(),
+    fn parse(input: &str) -> anyhow::Result<Point> {
+        let (x, y) = input
+            .split_once(',')
+            .ok_or_else(|| anyhow!("invalid point: {}", input))?;
+        let point = Point {
+            x: x.parse()?,
+            y: y.parse()?,
+        };
+        if point.x >= MAX_COORDINATE || point.y >= MAX_COORDINATE {
+            bail!("point outside of the diagram: {}", input);
         }
+        Ok(point)
     }
 }
 
 #[derive(Debug, Clone)]
-struct Line {
+pub(crate) struct Line {
     begin: Point,
     end: Point,
 }
 
 impl Line {
-    fn parse(input: &str) -> Line {
-        let parts: Vec<&str> = input.split(" -> ").collect();
-        Line {
-            begin: Point::parse(parts[0]),
-            end: Point::parse(parts[1]),
+    fn parse(input: &str) -> anyhow::Result<Line> {
+        let (begin, end) = input
+            .split_once(" -> ")
+            .ok_or_else(|| anyhow!("invalid line: {}", input))?;
+        let line = Line {
+            begin: Point::parse(begin)?,
+            end: Point::parse(end)?,
+        };
+        if line.is_diagonal()
+            && line.begin.x.abs_diff(line.end.x) != line.begin.y.abs_diff(line.end.y)
+        {
+            bail!("diagonal line not at 45 degrees: {}", input);
         }
+        Ok(line)
     }
 
     fn is_diagonal(&self) -> bool {
@@ -211,10 +229,11 @@ fn grid_size(lines: &[Line]) -> (usize, usize) {
 
 fn build_grid(lines: &[Line]) -> Grid<u8> {
     let (cols, rows) = grid_size(lines);
-    let mut grid = Grid::new(rows, cols);
+    let mut grid: Grid<u8> = Grid::new(rows, cols);
     for line in lines {
         for point in line.build_points() {
-            grid[point.y][point.x] += 1;
+            // only whether at least two lines overlap matters
+            grid[point.y][point.x] = grid[point.y][point.x].saturating_add(1);
         }
     }
     grid
@@ -224,6 +243,21 @@ fn build_grid(lines: &[Line]) -> Grid<u8> {
 mod tests {
     use super::*;
 
+    /// the diagram of the puzzle description, `.` for points not covered by any line
+    fn diagram(grid: &Grid<u8>) -> String {
+        (0..grid.rows())
+            .map(|y| {
+                grid.iter_row(y)
+                    .map(|&count| match count {
+                        0 => '.',
+                        _ => char::from_digit(count as u32, 36).unwrap_or('+'),
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
     const EXAMPLE: &str = "0,9 -> 5,9
 8,0 -> 0,8
 9,4 -> 3,4
@@ -239,15 +273,31 @@ mod tests {
     fn part1_examples() {
         // In the above example, this is anywhere in the diagram with a `2` or
         // larger - a total of `5` points.
-        let lines = parse_input(EXAMPLE);
-        assert_eq!(part1(&lines), 5);
+        let lines = parse_input(EXAMPLE).unwrap();
+        let non_diagonals: Vec<Line> = lines.iter().filter(|l| !l.is_diagonal()).cloned().collect();
+        insta::assert_snapshot!(diagram(&build_grid(&non_diagonals)));
+        assert_eq!(part1(&lines), Answer::Int(5));
     }
 
     #[test]
     fn part2_examples() {
         // In the above example, this is still anywhere in the diagram with a `2` or larger - now a
         // total of `12` points.
-        let lines = parse_input(EXAMPLE);
-        assert_eq!(part2(&lines), 12);
+        let lines = parse_input(EXAMPLE).unwrap();
+        insta::assert_snapshot!(diagram(&build_grid(&lines)));
+        assert_eq!(part2(&lines), Answer::Int(12));
+    }
+
+    #[test]
+    fn parse_errors() {
+        let error = |input| parse_input(input).unwrap_err().to_string();
+        assert_eq!(
+            error("0,0 -> 8,8\n0,0 -> 2,1"),
+            "diagonal line not at 45 degrees: 0,0 -> 2,1"
+        );
+        assert_eq!(
+            error("0,0 -> 9999,0"),
+            "point outside of the diagram: 9999,0"
+        );
     }
 }
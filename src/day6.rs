@@ -84,37 +84,51 @@
 //! After 256 days in the example above, there would be a total of `26984457539` lanternfish!
 //! **How many lanternfish would there be after 256 days?**
 
+use crate::answer::Answer;
+use crate::simulation::Simulation;
+
 #[aoc_generator(day6)]
-fn parse_input(input: &str) -> Vec<u8> {
-    input.split(',').map(|line| line.parse().unwrap()).collect()
+pub(crate) fn parse_input(input: &str) -> anyhow::Result<Vec<u8>> {
+    input
+        .split(',')
+        .map(|timer| match timer.parse()? {
+            timer @ 0..=MAX_TIMER => Ok(timer),
+            timer => bail!("timer {} is larger than {}", timer, MAX_TIMER),
+        })
+        .collect()
 }
 
+/// timer of newborn fish, no fish starts with a larger one
+const MAX_TIMER: u8 = 8;
+
 /// Part 1: How many lanternfish would there be after 80 days?
 #[aoc(day6, part1)]
-fn part1(input: &[u8]) -> u64 {
-    evolve(input, 80)
+pub(crate) fn part1(input: &[u8]) -> Answer {
+    evolve(input, 80).into()
 }
 
 /// Part 2: How many lanternfish would there be after 256 days?
 #[aoc(day6, part2)]
-fn part2(input: &[u8]) -> u64 {
-    evolve(input, 256)
+pub(crate) fn part2(input: &[u8]) -> Answer {
+    evolve(input, 256).into()
 }
 
-fn evolve(input: &[u8], days: u64) -> u64 {
+fn evolve(input: &[u8], days: usize) -> u64 {
     let mut count = [0u64; 9];
     for i in input {
         count[*i as usize] += 1;
     }
-    for _ in 0..days {
-        let growth = count[0];
-        for i in 1..=8 {
-            count[i - 1] = count[i];
-        }
-        count[8] = growth;
-        count[6] += growth;
-    }
-    count.iter().sum()
+    Simulation::new(count, next_day).run(days).iter().sum()
+}
+
+/// fish counts by timer value, each fish with timer `0` creates a new fish with timer `8` and
+/// restarts at `6`
+fn next_day(count: &[u64; 9]) -> [u64; 9] {
+    let mut next = [0u64; 9];
+    next[..8].copy_from_slice(&count[1..]);
+    next[8] = count[0];
+    next[6] += count[0];
+    next
 }
 
 #[cfg(test)]
@@ -125,7 +139,7 @@ mod tests {
 
     #[test]
     fn part1_examples() {
-        let state = parse_input(EXAMPLE);
+        let state = parse_input(EXAMPLE).unwrap();
         // In this example, after 18 days, there are a total of `26` fish.
         assert_eq!(evolve(&state, 18), 26);
 
@@ -136,7 +150,15 @@ mod tests {
     #[test]
     fn part2_examples() {
         // After 256 days in the example above, there would be a total of `26984457539` lanternfish!
-        let state = parse_input(EXAMPLE);
+        let state = parse_input(EXAMPLE).unwrap();
         assert_eq!(evolve(&state, 256), 26984457539);
     }
+
+    #[test]
+    fn parse_errors() {
+        let error = |input| parse_input(input).unwrap_err().to_string();
+        assert_eq!(error("3,9,1"), "timer 9 is larger than 8");
+        assert_eq!(error("3,x"), "invalid digit found in string");
+        assert_eq!(parse_input("8,0").unwrap(), [8, 0]);
+    }
 }
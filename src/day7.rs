@@ -79,20 +79,35 @@
 //!
 //! **How much fuel must they spend to align to that position?**
 
+use crate::answer::Answer;
+
 #[aoc_generator(day7)]
-fn parse_input(input: &str) -> Vec<u32> {
-    input.split(',').map(|line| line.parse().unwrap()).collect()
+pub(crate) fn parse_input(input: &str) -> anyhow::Result<Vec<u32>> {
+    input
+        .split(',')
+        .map(|line| {
+            let position = line.parse()?;
+            if position > MAX_POSITION {
+                bail!("position {} is larger than {}", position, MAX_POSITION);
+            }
+            Ok(position)
+        })
+        .collect()
 }
 
+/// upper bound for the horizontal positions, real inputs stay below 2000
+const MAX_POSITION: u32 = 1 << 16;
+
 /// Part 1:
 /// Determine the horizontal position that the crabs can align to using the least fuel possible.
 /// How much fuel must they spend to align to that position?
 #[aoc(day7, part1)]
-fn part1(input: &[u32]) -> u32 {
+pub(crate) fn part1(input: &[u32]) -> Answer {
     (0..input.len())
         .map(|target| fuel1(input, target as u32))
         .min()
         .unwrap()
+        .into()
 }
 
 /// Part 2:
@@ -100,44 +115,35 @@ fn part1(input: &[u32]) -> u32 {
 /// they can make you an escape route!
 /// How much fuel must they spend to align to that position?
 #[aoc(day7, part2)]
-fn part2(input: &[u32]) -> u32 {
+pub(crate) fn part2(input: &[u32]) -> Answer {
     (0..input.len())
         .map(|target| fuel2(input, target as u32))
         .min()
         .unwrap()
+        .into()
 }
 
-fn fuel1(input: &[u32], target: u32) -> u32 {
+fn fuel1(input: &[u32], target: u32) -> u64 {
     let mut fuel = 0;
     for v in input {
-        fuel += abs_diff(*v, target);
+        fuel += v.abs_diff(target) as u64;
     }
     fuel
 }
 
-fn fuel2(input: &[u32], target: u32) -> u32 {
+fn fuel2(input: &[u32], target: u32) -> u64 {
     let mut fuel = 0;
     for v in input {
-        fuel += triangle_number(abs_diff(*v, target));
+        fuel += triangle_number(v.abs_diff(target) as u64);
     }
     fuel
 }
 
 // https://en.wikipedia.org/wiki/Triangular_number
-fn triangle_number(n: u32) -> u32 {
+fn triangle_number(n: u64) -> u64 {
     n * (n + 1) / 2
 }
 
-// named after nightly-only u32::abs_diff:
-// https://doc.rust-lang.org/std/primitive.u32.html#method.abs_diff
-fn abs_diff(a: u32, b: u32) -> u32 {
-    if a > b {
-        a - b
-    } else {
-        b - a
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -147,12 +153,24 @@ mod tests {
     #[test]
     fn part1_examples() {
         // This costs a total of `37` fuel. This is the cheapest possible outcome
-        assert_eq!(part1(&parse_input(EXAMPLE)), 37);
+        assert_eq!(part1(&parse_input(EXAMPLE).unwrap()), Answer::Int(37));
     }
 
     #[test]
     fn part2_examples() {
         //  This costs a total of `168` fuel. This is the new cheapest possible outcome
-        assert_eq!(part2(&parse_input(EXAMPLE)), 168);
+        assert_eq!(part2(&parse_input(EXAMPLE).unwrap()), Answer::Int(168));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            parse_input("100000,0").unwrap_err().to_string(),
+            "position 100000 is larger than 65536"
+        );
+        assert_eq!(
+            part2(&parse_input("65536,0").unwrap()),
+            Answer::Int(2147450880 + 1)
+        );
     }
 }
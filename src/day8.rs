@@ -171,26 +171,112 @@
 //! For each entry, determine all of the wire/segment connections and decode the
 //! four-digit output values. **What do you get if you add up all of the output values?**
 
-use std::collections::HashMap;
+use crate::answer::Answer;
+use crate::validate::Violation;
 
 #[aoc_generator(day8)]
-fn parse_input(input: &str) -> Vec<(Vec<String>, Vec<String>)> {
+pub(crate) fn parse_input(input: &str) -> anyhow::Result<Vec<(Vec<String>, Vec<String>)>> {
     input
         .lines()
         .map(|line| {
-            let parts: Vec<&str> = line.split(" | ").collect();
-            (
-                parts[0].split(' ').map(sort).collect(),
-                parts[1].split(' ').map(sort).collect(),
-            )
+            let (patterns, outputs) = line
+                .split_once(" | ")
+                .ok_or_else(|| anyhow!("invalid entry: {}", line))?;
+            if let Some(c) = line.chars().find(|c| !matches!(c, 'a'..='g' | ' ' | '|')) {
+                bail!("invalid segment `{}` in entry: {}", c, line);
+            }
+            Ok((
+                patterns.split(' ').map(sort).collect(),
+                outputs.split(' ').map(sort).collect(),
+            ))
         })
         .collect()
 }
 
+/// Checks every entry consists of exactly ten unique signal patterns, which together use the
+/// segment counts of the digits `0` to `9`, and four output values found among these patterns.
+pub(crate) fn validate(input: &str) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    for (idx, line) in input.lines().enumerate() {
+        let nr = idx + 1;
+        let (patterns, outputs) = match line.split_once(" | ") {
+            Some(parts) => parts,
+            None => {
+                violations.push(Violation::at_line(nr, "missing ` | ` separator"));
+                continue;
+            }
+        };
+        let mut offset = 0;
+        for word in line.split(' ') {
+            let mut seen = String::new();
+            for (i, c) in word.chars().enumerate().filter(|_| word != "|") {
+                if !('a'..='g').contains(&c) {
+                    violations.push(Violation::at(
+                        nr,
+                        offset + i + 1,
+                        format!("invalid segment `{}`", c),
+                    ));
+                } else if seen.contains(c) {
+                    violations.push(Violation::at(
+                        nr,
+                        offset + i + 1,
+                        format!("repeated segment `{}`", c),
+                    ));
+                }
+                seen.push(c);
+            }
+            offset += word.len() + 1;
+        }
+
+        let patterns: Vec<String> = patterns.split(' ').map(sort).collect();
+        let outputs: Vec<&str> = outputs.split(' ').collect();
+        if patterns.len() != 10 {
+            violations.push(Violation::at_line(
+                nr,
+                format!("expected 10 unique patterns, found {}", patterns.len()),
+            ));
+        }
+        if outputs.len() != 4 {
+            violations.push(Violation::at_line(
+                nr,
+                format!("expected 4 output values, found {}", outputs.len()),
+            ));
+        }
+        for (i, pattern) in patterns.iter().enumerate() {
+            if patterns[..i].contains(pattern) {
+                violations.push(Violation::at_line(
+                    nr,
+                    format!("pattern `{}` is not unique", pattern),
+                ));
+            }
+        }
+        let mut segment_counts: Vec<usize> = patterns.iter().map(|p| p.len()).collect();
+        segment_counts.sort_unstable();
+        if patterns.len() == 10 && segment_counts != [2, 3, 4, 5, 5, 5, 6, 6, 6, 7] {
+            violations.push(Violation::at_line(
+                nr,
+                format!(
+                    "segment counts {:?} do not match the digits 0-9",
+                    segment_counts
+                ),
+            ));
+        }
+        for output in outputs {
+            if !patterns.contains(&sort(output)) {
+                violations.push(Violation::at_line(
+                    nr,
+                    format!("output `{}` matches no pattern", output),
+                ));
+            }
+        }
+    }
+    violations
+}
+
 /// Part 1: In the output values, how many times do digits `1`, `4`, `7`, or `8` appear?
 #[aoc(day8, part1)]
-fn part1(input: &[(Vec<String>, Vec<String>)]) -> usize {
-    let mut cnt = 0;
+pub(crate) fn part1(input: &[(Vec<String>, Vec<String>)]) -> Answer {
+    let mut cnt = 0usize;
     // 1: 2 segments
     // 7: 3 segments
     // 4: 4 segments
@@ -203,16 +289,19 @@ fn part1(input: &[(Vec<String>, Vec<String>)]) -> usize {
             }
         }
     }
-    cnt
+    cnt.into()
 }
 
 /// Part 2: What do you get if you add up all of the output values?
 #[aoc(day8, part2)]
-fn part2(input: &[(Vec<String>, Vec<String>)]) -> u64 {
-    input
-        .iter()
-        .map(|(unique_signal_patterns, value)| decode(unique_signal_patterns, value))
-        .sum()
+pub(crate) fn part2(input: &[(Vec<String>, Vec<String>)]) -> anyhow::Result<Answer> {
+    let mut sum = 0u64;
+    for (unique_signal_patterns, value) in input {
+        sum = sum
+            .checked_add(decode(unique_signal_patterns, value)?)
+            .ok_or_else(|| anyhow!("sum of output values overflows"))?;
+    }
+    Ok(sum.into())
 }
 
 fn sort(s: &str) -> String {
@@ -221,96 +310,78 @@ fn sort(s: &str) -> String {
     String::from_iter(chars)
 }
 
-// returns true if every char of needles if present in the haystack
-fn string_contains_chars(haystack: &str, needles: &str) -> bool {
-    for c in needles.chars() {
-        if !haystack.contains(c) {
-            return false;
-        }
-    }
-    true
+/// segments of a pattern as bits, `a` being the lowest one
+fn segments(pattern: &str) -> u8 {
+    pattern.bytes().fold(0, |mask, c| mask | 1 << (c - b'a'))
 }
 
-fn build_map(unique_signal_patterns: &[String]) -> HashMap<String, u64> {
-    let mut output = HashMap::new();
-
-    let signal1 = unique_signal_patterns
-        .iter()
-        .find(|s| s.len() == 2)
-        .expect("failed to find 1");
-    output.insert(signal1.to_string(), 1); // ab: 1
-
-    let signal7 = unique_signal_patterns
-        .iter()
-        .find(|s| s.len() == 3)
-        .expect("failed to find 7");
-    output.insert(signal7.to_string(), 7); // dab: 7
+// returns true if every segment of needles is present in the haystack
+fn contains_segments(haystack: u8, needles: u8) -> bool {
+    haystack & needles == needles
+}
 
-    let signal4 = unique_signal_patterns
-        .iter()
-        .find(|s| s.len() == 4)
-        .expect("failed to find 4");
-    output.insert(signal4.to_string(), 4); // eafb: 4
+/// digit of every pattern, indexed by the segment bits of the pattern
+fn build_map(unique_signal_patterns: &[String]) -> anyhow::Result<[Option<u8>; 128]> {
+    let patterns: Vec<u8> = unique_signal_patterns.iter().map(|s| segments(s)).collect();
+    let find = |digit: u8, predicate: &dyn Fn(u8) -> bool| {
+        patterns
+            .iter()
+            .copied()
+            .find(|&s| predicate(s))
+            .ok_or_else(|| anyhow!("failed to find {}", digit))
+    };
+    let len = |s: u8| s.count_ones();
 
-    let signal8 = unique_signal_patterns
-        .iter()
-        .find(|s| s.len() == 7)
-        .expect("failed to find 8");
-    output.insert(signal8.to_string(), 8); // acedgfb: 8
+    let signal1 = find(1, &|s| len(s) == 2)?; // ab: 1
+    let signal7 = find(7, &|s| len(s) == 3)?; // dab: 7
+    let signal4 = find(4, &|s| len(s) == 4)?; // eafb: 4
+    let signal8 = find(8, &|s| len(s) == 7)?; // acedgfb: 8
 
     // 3 should be a 1 with an additional segment
-    let signal3 = unique_signal_patterns
-        .iter()
-        .find(|s| s.len() == 5 && string_contains_chars(s, signal1))
-        .expect("failed to find 3");
-    output.insert(signal3.to_string(), 3); // fbcad: 3
+    let signal3 = find(3, &|s| len(s) == 5 && contains_segments(s, signal1))?; // fbcad: 3
 
     // 9 should be 3 with 1 additional segment
-    let signal9 = unique_signal_patterns
-        .iter()
-        .find(|s| s.len() == 6 && string_contains_chars(s, signal3))
-        .expect("failed to find 9");
-    output.insert(signal9.to_string(), 9); // cefabd: 9
+    let signal9 = find(9, &|s| len(s) == 6 && contains_segments(s, signal3))?; // cefabd: 9
 
     // 0 should contain 1
-    let signal0 = unique_signal_patterns
-        .iter()
-        .find(|s| s.len() == 6 && !s.eq(&signal9) && string_contains_chars(s, signal1))
-        .expect("failed to find 0");
-    output.insert(signal0.to_string(), 0); // cagedb: 0
+    let signal0 = find(0, &|s| {
+        len(s) == 6 && s != signal9 && contains_segments(s, signal1)
+    })?; // cagedb: 0
 
     // 6 should have 6 segments
-    let signal6 = unique_signal_patterns
-        .iter()
-        .find(|s| s.len() == 6 && !s.eq(&signal9) && !s.eq(&signal0))
-        .expect("failed to find 6");
-    output.insert(signal6.to_string(), 6); // cdfgeb: 6
+    let signal6 = find(6, &|s| len(s) == 6 && s != signal9 && s != signal0)?; // cdfgeb: 6
 
     // 5 is included in 6
-    let signal5 = unique_signal_patterns
-        .iter()
-        .find(|s| s.len() == 5 && !s.eq(&signal3) && string_contains_chars(signal6, s))
-        .expect("failed to find 5");
-    output.insert(signal5.to_string(), 5); // cdfbe: 5
+    let signal5 = find(5, &|s| {
+        len(s) == 5 && s != signal3 && contains_segments(signal6, s)
+    })?; // cdfbe: 5
 
     // 2 is not 3 or 5
-    let signal6 = unique_signal_patterns
-        .iter()
-        .find(|s| s.len() == 5 && !s.eq(&signal3) && !s.eq(&signal5))
-        .expect("failed to find 2");
-    output.insert(signal6.to_string(), 2); // gcdfa: 2
+    let signal2 = find(2, &|s| len(s) == 5 && s != signal3 && s != signal5)?; // gcdfa: 2
 
-    assert_eq!(10, output.len());
-    output
+    let mut output = [None; 128];
+    let signals = [
+        signal0, signal1, signal2, signal3, signal4, signal5, signal6, signal7, signal8, signal9,
+    ];
+    for (digit, signal) in (0..).zip(signals) {
+        output[signal as usize] = Some(digit);
+    }
+    if output.iter().flatten().count() != 10 {
+        bail!("digits share signal patterns");
+    }
+    Ok(output)
 }
 
-fn decode(unique_signal_patterns: &[String], value: &[String]) -> u64 {
-    let map = build_map(unique_signal_patterns);
-    let mut output = String::new();
-    for digit in value {
-        output += &*map.get(digit).unwrap().to_string();
-    }
-    output.parse().unwrap()
+fn decode(unique_signal_patterns: &[String], value: &[String]) -> anyhow::Result<u64> {
+    let map = build_map(unique_signal_patterns)?;
+    value.iter().try_fold(0u64, |output, digit| {
+        let digit = map[segments(digit) as usize]
+            .ok_or_else(|| anyhow!("unknown output pattern `{}`", digit))?;
+        output
+            .checked_mul(10)
+            .and_then(|output| output.checked_add(digit as u64))
+            .ok_or_else(|| anyhow!("output value too large"))
+    })
 }
 
 #[cfg(test)]
@@ -329,9 +400,22 @@ bdfegc cbegaf gecbf dfcage bdacg ed bedf ced adcbefg gebcd | ed bcgafe cdgba cbg
 egadfb cdbfeg cegd fecab cgb gbdefca cg fgcdab egfdb bfceg | gbdfcae bgc cg cgb
 gcafb gcf dcaebfg ecagb gf abcdeg gaef cafbge fdbac fegbdc | fgae cfgab fg bagce";
 
+    #[test]
+    fn validate_examples() {
+        assert_eq!(validate(EXAMPLE), vec![]);
+        assert_eq!(
+            validate("be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd | fdgacbe cefdb cefbgd gcxe"),
+            vec![
+                Violation::at(1, 81, "invalid segment `x`"),
+                Violation::at_line(1, "expected 10 unique patterns, found 9"),
+                Violation::at_line(1, "output `gcxe` matches no pattern"),
+            ]
+        );
+    }
+
     #[test]
     fn part1_examples() {
-        assert_eq!(part1(&parse_input(EXAMPLE)), 26);
+        assert_eq!(part1(&parse_input(EXAMPLE).unwrap()), Answer::Int(26));
     }
 
     #[test]
@@ -339,9 +423,19 @@ gcafb gcf dcaebfg ecagb gf abcdeg gaef cafbge fdbac fegbdc | fgae cfgab fg bagce
         assert_eq!(
             part2(&parse_input(
                 "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf"
-            )),
-            5353
+            ).unwrap())
+            .unwrap(),
+            Answer::Int(5353)
+        );
+        assert_eq!(
+            part2(&parse_input(EXAMPLE).unwrap()).unwrap(),
+            Answer::Int(61229)
+        );
+        assert_eq!(
+            part2(&parse_input("a b c d e f g ab abc abcd | ab").unwrap())
+                .unwrap_err()
+                .to_string(),
+            "failed to find 8"
         );
-        assert_eq!(part2(&parse_input(EXAMPLE)), 61229);
     }
 }
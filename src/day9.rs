@@ -96,27 +96,56 @@
 //!
 //! **What do you get if you multiply together the sizes of the three largest basins?**
 
+use crate::answer::Answer;
+use crate::explain::Explanation;
 use crate::util::{get_neighbors4, parse_array2};
 use ndarray::Array2;
 
 #[aoc_generator(day9)]
-fn parse_input(input: &str) -> Array2<u8> {
+pub(crate) fn parse_input(input: &str) -> anyhow::Result<Array2<u8>> {
     parse_array2(input)
 }
 
 /// Part 1: What is the sum of the risk levels of all low points on your heightmap?
 #[aoc(day9, part1)]
-fn part1(grid: &Array2<u8>) -> u64 {
-    find_low_points(grid).iter().sum()
+pub(crate) fn part1(grid: &Array2<u8>) -> Answer {
+    find_low_points(grid).iter().sum::<u64>().into()
 }
 
 /// Part 2: What do you get if you multiply together the sizes of the three largest basins?
 #[aoc(day9, part2)]
-fn part2(grid: &Array2<u8>) -> u64 {
-    let mut basins = find_basins(grid);
+pub(crate) fn part2(grid: &Array2<u8>) -> anyhow::Result<Answer> {
+    let mut basins: Vec<u64> = find_basins(grid)
+        .into_iter()
+        .map(|(_, size)| size)
+        .collect();
+    if basins.len() < 3 {
+        bail!("only {} basins, three are needed", basins.len());
+    }
     basins.sort_unstable();
     basins.reverse();
-    basins[0..3].iter().product()
+    Ok(basins[0..3].iter().product::<u64>().into())
+}
+
+/// all basins by their low point, largest first
+pub(crate) fn explain2(grid: &Array2<u8>) -> Explanation {
+    let mut basins = find_basins(grid);
+    basins.sort_by(|(_, a), (_, b)| b.cmp(a));
+    let rows = basins
+        .iter()
+        .map(|((y, x), size)| {
+            vec![
+                format!("({},{})", y, x),
+                grid[(*y, *x)].to_string(),
+                size.to_string(),
+            ]
+        })
+        .collect();
+    let largest: Vec<u64> = basins.iter().take(3).map(|(_, size)| *size).collect();
+    Explanation::new()
+        .table("basins", &["low point", "height", "size"], rows)
+        .value("three largest", format!("{:?}", largest))
+        .value("product", largest.iter().product::<u64>())
 }
 
 fn find_low_points(grid: &Array2<u8>) -> Vec<u64> {
@@ -160,7 +189,8 @@ fn explore_basin(grid: &Array2<u8>, start: (usize, usize)) -> u64 {
     already_visited.len() as u64
 }
 
-fn find_basins(grid: &Array2<u8>) -> Vec<u64> {
+/// low point and size of every basin
+fn find_basins(grid: &Array2<u8>) -> Vec<((usize, usize), u64)> {
     let mut basins = Vec::new();
     for (y, row) in grid.rows().into_iter().enumerate() {
         for (x, col) in row.iter().enumerate() {
@@ -175,7 +205,7 @@ fn find_basins(grid: &Array2<u8>) -> Vec<u64> {
             if higher_found {
                 continue;
             }
-            basins.push(explore_basin(grid, (y, x)));
+            basins.push(((y, x), explore_basin(grid, (y, x))));
         }
     }
     basins
@@ -184,6 +214,7 @@ fn find_basins(grid: &Array2<u8>) -> Vec<u64> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::snapshot;
 
     const EXAMPLE: &str = "2199943210
 3987894921
@@ -193,11 +224,51 @@ mod tests {
 
     #[test]
     fn part1_examples() {
-        assert_eq!(part1(&parse_input(EXAMPLE)), 15);
+        assert_eq!(part1(&parse_input(EXAMPLE).unwrap()), Answer::Int(15));
+    }
+
+    #[test]
+    fn low_points_and_basins() {
+        let grid = parse_input(EXAMPLE).unwrap();
+        let lows: Vec<(usize, usize)> = find_basins(&grid).into_iter().map(|(p, _)| p).collect();
+        let low_points = snapshot::grid(&grid, |pos, height| match lows.contains(&pos) {
+            true => char::from(b'0' + height),
+            false => '.',
+        });
+        let borders = snapshot::grid(&grid, |_, &height| if height == 9 { '#' } else { '.' });
+        insta::assert_snapshot!(snapshot::states([
+            ("low points", low_points),
+            ("basin borders", borders)
+        ]));
     }
 
     #[test]
     fn part2_examples() {
-        assert_eq!(part2(&parse_input(EXAMPLE)), 1134);
+        assert_eq!(
+            part2(&parse_input(EXAMPLE).unwrap()).unwrap(),
+            Answer::Int(1134)
+        );
+        assert_eq!(
+            part2(&parse_input("191\n999").unwrap())
+                .unwrap_err()
+                .to_string(),
+            "only 2 basins, three are needed"
+        );
+    }
+
+    #[test]
+    fn explain_examples() {
+        assert_eq!(
+            explain2(&parse_input(EXAMPLE).unwrap()).to_string(),
+            "basins:
+  low point | height | size
+      (2,2) |      5 |   14
+      (0,9) |      0 |    9
+      (4,6) |      5 |    9
+      (0,1) |      1 |    3
+three largest: [14, 9, 9]
+product: 1134
+"
+        );
     }
 }
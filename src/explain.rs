@@ -0,0 +1,147 @@
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+
+/// Intermediate results of a puzzle part, shown by the runner with `--explain`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(transparent)]
+pub struct Explanation {
+    pub entries: Vec<Entry>,
+}
+
+/// A single named intermediate result
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Entry {
+    pub name: String,
+    #[serde(flatten)]
+    pub detail: Detail,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Detail {
+    /// single value, already formatted for display
+    Value(String),
+    /// rows of values below a header line
+    Table {
+        header: Vec<String>,
+        rows: Vec<Vec<String>>,
+    },
+    /// positions as `(row, column)`, in the order they are visited
+    Path(Vec<(usize, usize)>),
+}
+
+impl Explanation {
+    pub fn new() -> Explanation {
+        Explanation::default()
+    }
+
+    /// adds a named value
+    pub fn value<V: Display>(mut self, name: &str, value: V) -> Explanation {
+        self.push(name, Detail::Value(value.to_string()));
+        self
+    }
+
+    /// adds a named table, every row should have as many cells as the header
+    pub fn table(mut self, name: &str, header: &[&str], rows: Vec<Vec<String>>) -> Explanation {
+        let header = header.iter().map(|h| h.to_string()).collect();
+        self.push(name, Detail::Table { header, rows });
+        self
+    }
+
+    /// adds a named path of grid positions
+    pub fn path(mut self, name: &str, path: Vec<(usize, usize)>) -> Explanation {
+        self.push(name, Detail::Path(path));
+        self
+    }
+
+    fn push(&mut self, name: &str, detail: Detail) {
+        self.entries.push(Entry {
+            name: name.to_string(),
+            detail,
+        });
+    }
+}
+
+impl Display for Explanation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for entry in &self.entries {
+            match &entry.detail {
+                Detail::Value(value) => writeln!(f, "{}: {}", entry.name, value)?,
+                Detail::Table { header, rows } => {
+                    writeln!(f, "{}:", entry.name)?;
+                    write_table(f, header, rows)?;
+                }
+                Detail::Path(path) => {
+                    let steps: Vec<String> =
+                        path.iter().map(|(y, x)| format!("({},{})", y, x)).collect();
+                    writeln!(
+                        f,
+                        "{} ({} positions): {}",
+                        entry.name,
+                        path.len(),
+                        steps.join(" ")
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// writes an indented table with right aligned columns
+fn write_table(f: &mut Formatter<'_>, header: &[String], rows: &[Vec<String>]) -> std::fmt::Result {
+    let mut widths: Vec<usize> = header.iter().map(|h| h.len()).collect();
+    for row in rows {
+        for (i, cell) in row.iter().enumerate() {
+            if i < widths.len() {
+                widths[i] = widths[i].max(cell.len());
+            }
+        }
+    }
+    for row in std::iter::once(header).chain(rows.iter().map(|r| r.as_slice())) {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:>width$}", cell, width = width))
+            .collect();
+        writeln!(f, "  {}", cells.join(" | "))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> Explanation {
+        Explanation::new()
+            .value("gamma", 22)
+            .table(
+                "basins",
+                &["low point", "size"],
+                vec![vec!["(0,1)".into(), "3".into()]],
+            )
+            .path("path", vec![(0, 0), (1, 0)])
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(
+            example().to_string(),
+            "gamma: 22
+basins:
+  low point | size
+      (0,1) |    3
+path (2 positions): (0,0) (1,0)
+"
+        );
+    }
+
+    #[test]
+    fn json() {
+        assert_eq!(
+            serde_json::to_string(&example()).unwrap(),
+            r#"[{"name":"gamma","value":"22"},{"name":"basins","table":{"header":["low point","size"],"rows":[["(0,1)","3"]]}},{"name":"path","path":[[0,0],[1,0]]}]"#
+        );
+    }
+}
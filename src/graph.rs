@@ -0,0 +1,353 @@
+//! Building petgraph graphs from grids or named connections and exporting graphs to Graphviz DOT
+
+use crate::util::{get_neighbors4, get_neighbors8};
+use ndarray::Array2;
+use petgraph::graph::{NodeIndex, UnGraph};
+use petgraph::visit::EdgeRef;
+use petgraph::EdgeType;
+use petgraph::Graph;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+pub type Pos = (usize, usize);
+
+/// neighbor positions of a position in a grid
+pub type Neighbors<T> = Box<dyn Fn(&Array2<T>, &Pos) -> Vec<Pos>>;
+
+/// edge weight from the source and target cell
+type Weight<'a, T, E> = Box<dyn Fn(&T, &T) -> E + 'a>;
+
+/// Which cells are connected to each other
+pub enum Connectivity<T> {
+    /// horizontal and vertical neighbors
+    Four,
+    /// horizontal, vertical and diagonal neighbors
+    Eight,
+    /// neighbors returned by a function, like the ones in `util`
+    Custom(Neighbors<T>),
+}
+
+/// Configurable conversion of a grid into a graph, with one node per passable cell
+///
+/// Nodes are weighted with their position in the grid, edges with the result of the weight
+/// function called with the source and target cell.
+pub struct GraphBuilder<'a, T, E> {
+    grid: &'a Array2<T>,
+    weight: Weight<'a, T, E>,
+    connectivity: Connectivity<T>,
+    passable: Box<dyn Fn(&T) -> bool + 'a>,
+}
+
+/// A graph built from a grid, with the node of every passable cell
+pub struct GridGraph<E, Ty: EdgeType> {
+    pub graph: Graph<Pos, E, Ty>,
+    pub nodes: Array2<Option<NodeIndex>>,
+}
+
+impl<'a, T, E> GraphBuilder<'a, T, E> {
+    /// builder for a 4-connected graph of all cells
+    pub fn new<W>(grid: &'a Array2<T>, weight: W) -> GraphBuilder<'a, T, E>
+    where
+        W: Fn(&T, &T) -> E + 'a,
+    {
+        GraphBuilder {
+            grid,
+            weight: Box::new(weight),
+            connectivity: Connectivity::Four,
+            passable: Box::new(|_| true),
+        }
+    }
+
+    pub fn connectivity(mut self, connectivity: Connectivity<T>) -> GraphBuilder<'a, T, E> {
+        self.connectivity = connectivity;
+        self
+    }
+
+    /// only cells matching the predicate become nodes
+    pub fn passable<P>(mut self, passable: P) -> GraphBuilder<'a, T, E>
+    where
+        P: Fn(&T) -> bool + 'a,
+    {
+        self.passable = Box::new(passable);
+        self
+    }
+
+    /// directed graph with an edge from every cell to each of its neighbors
+    pub fn build(&self) -> GridGraph<E, petgraph::Directed> {
+        let mut result = self.nodes(Graph::new());
+        for (from, to) in self.neighbor_pairs(&result.nodes) {
+            let weight = (self.weight)(&self.grid[from], &self.grid[to]);
+            let (a, b) = (result.nodes[from].unwrap(), result.nodes[to].unwrap());
+            result.graph.update_edge(a, b, weight);
+        }
+        result
+    }
+
+    /// undirected graph with a single edge between neighbors, weighted from the cell
+    /// which comes first in row major order to the other one
+    pub fn build_undirected(&self) -> GridGraph<E, petgraph::Undirected> {
+        let mut result = self.nodes(UnGraph::default());
+        for (from, to) in self.neighbor_pairs(&result.nodes) {
+            let (from, to) = (from.min(to), from.max(to));
+            let (a, b) = (result.nodes[from].unwrap(), result.nodes[to].unwrap());
+            if result.graph.find_edge(a, b).is_none() {
+                let weight = (self.weight)(&self.grid[from], &self.grid[to]);
+                result.graph.add_edge(a, b, weight);
+            }
+        }
+        result
+    }
+
+    fn nodes<Ty: EdgeType>(&self, mut graph: Graph<Pos, E, Ty>) -> GridGraph<E, Ty> {
+        let nodes = Array2::from_shape_fn(self.grid.raw_dim(), |pos| {
+            (self.passable)(&self.grid[pos]).then(|| graph.add_node(pos))
+        });
+        GridGraph { graph, nodes }
+    }
+
+    /// pairs of passable neighbors, every pair in both directions for symmetric connectivity
+    fn neighbor_pairs(&self, nodes: &Array2<Option<NodeIndex>>) -> Vec<(Pos, Pos)> {
+        let mut pairs = Vec::new();
+        for (from, node) in nodes.indexed_iter() {
+            if node.is_none() {
+                continue;
+            }
+            let neighbors = match &self.connectivity {
+                Connectivity::Four => get_neighbors4(self.grid, &from),
+                Connectivity::Eight => get_neighbors8(self.grid, &from),
+                Connectivity::Custom(neighbors) => neighbors(self.grid, &from),
+            };
+            for to in neighbors {
+                if nodes.get(to).is_some_and(|n| n.is_some()) {
+                    pairs.push((from, to));
+                }
+            }
+        }
+        pairs
+    }
+}
+
+/// node weight from the name of the node
+type NodeWeight<'a, N> = Box<dyn Fn(&str) -> anyhow::Result<N> + 'a>;
+
+/// Conversion of connections between named nodes into a graph, with one node per name
+///
+/// Nodes are weighted with the result of the weight function called with their name when the
+/// name is first connected, every connection adds an edge in both directions.
+pub struct NamedGraphBuilder<'a, N, E> {
+    graph: Graph<N, E>,
+    nodes: HashMap<String, NodeIndex>,
+    weight: NodeWeight<'a, N>,
+}
+
+impl<'a, N, E: Clone> NamedGraphBuilder<'a, N, E> {
+    pub fn new<W>(weight: W) -> NamedGraphBuilder<'a, N, E>
+    where
+        W: Fn(&str) -> anyhow::Result<N> + 'a,
+    {
+        NamedGraphBuilder {
+            graph: Graph::new(),
+            nodes: HashMap::new(),
+            weight: Box::new(weight),
+        }
+    }
+
+    /// connects the nodes of both names, adding them if they are new
+    pub fn connect(&mut self, from: &str, to: &str, weight: E) -> anyhow::Result<()> {
+        let (from, to) = (self.node(from)?, self.node(to)?);
+        self.graph.add_edge(from, to, weight.clone());
+        self.graph.add_edge(to, from, weight);
+        Ok(())
+    }
+
+    fn node(&mut self, name: &str) -> anyhow::Result<NodeIndex> {
+        if let Some(idx) = self.nodes.get(name) {
+            return Ok(*idx);
+        }
+        let idx = self.graph.add_node((self.weight)(name)?);
+        self.nodes.insert(name.to_string(), idx);
+        Ok(idx)
+    }
+
+    pub fn build(self) -> Graph<N, E> {
+        self.graph
+    }
+}
+
+/// renders a graph as Graphviz DOT, labeling nodes and edges with given functions
+pub fn to_dot<N, E, Ty, NL, EL>(graph: &Graph<N, E, Ty>, node_label: NL, edge_label: EL) -> String
+where
+    Ty: EdgeType,
+    NL: Fn(&N) -> String,
+    EL: Fn(&E) -> String,
+{
+    write_dot(
+        graph,
+        |n| format!("label=\"{}\"", escape(&node_label(n))),
+        edge_label,
+    )
+}
+
+/// renders a grid graph as Graphviz DOT, with nodes pinned to their grid position for `neato -n`
+pub fn grid_to_dot<E, Ty, EL>(graph: &Graph<Pos, E, Ty>, edge_label: EL) -> String
+where
+    Ty: EdgeType,
+    EL: Fn(&E) -> String,
+{
+    let node_attributes = |(y, x): &Pos| format!("label=\"{},{}\" pos=\"{},-{}!\"", y, x, x, y);
+    write_dot(graph, node_attributes, edge_label)
+}
+
+fn write_dot<N, E, Ty, NA, EL>(
+    graph: &Graph<N, E, Ty>,
+    node_attributes: NA,
+    edge_label: EL,
+) -> String
+where
+    Ty: EdgeType,
+    NA: Fn(&N) -> String,
+    EL: Fn(&E) -> String,
+{
+    let (kind, arrow) = match graph.is_directed() {
+        true => ("digraph", "->"),
+        false => ("graph", "--"),
+    };
+    let mut dot = format!("{} {{\n", kind);
+    for node in graph.node_indices() {
+        writeln!(
+            dot,
+            "    {} [{}]",
+            node.index(),
+            node_attributes(&graph[node])
+        )
+        .unwrap();
+    }
+    for edge in graph.edge_references() {
+        let label = escape(&edge_label(edge.weight()));
+        let (source, target) = (edge.source().index(), edge.target().index());
+        match label.is_empty() {
+            true => writeln!(dot, "    {} {} {}", source, arrow, target),
+            false => writeln!(
+                dot,
+                "    {} {} {} [label=\"{}\"]",
+                source, arrow, target, label
+            ),
+        }
+        .unwrap();
+    }
+    dot += "}\n";
+    dot
+}
+
+fn escape(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::parse_array2;
+
+    #[test]
+    fn named_connections() {
+        let mut builder = NamedGraphBuilder::new(|name| match name.is_empty() {
+            true => bail!("empty name"),
+            false => Ok(name.to_uppercase()),
+        });
+        builder.connect("a", "b", 1).unwrap();
+        builder.connect("b", "c", 2).unwrap();
+        assert!(builder.connect("c", "", 3).is_err());
+        let graph = builder.build();
+        assert_eq!(graph.node_count(), 3);
+        assert_eq!(graph.edge_count(), 4);
+        let (b, c) = (NodeIndex::new(1), NodeIndex::new(2));
+        assert_eq!(graph[b], "B");
+        assert_eq!(graph[graph.find_edge(c, b).unwrap()], 2);
+    }
+
+    #[test]
+    fn dot_of_plain_graph() {
+        let mut graph: Graph<&str, ()> = Graph::new();
+        let start = graph.add_node("start");
+        let end = graph.add_node("\"end\"");
+        graph.add_edge(start, end, ());
+        assert_eq!(
+            to_dot(&graph, |n| n.to_string(), |_| String::new()),
+            "digraph {\n    0 [label=\"start\"]\n    1 [label=\"\\\"end\\\"\"]\n    0 -> 1\n}\n"
+        );
+    }
+
+    const EXAMPLE: &str = "131
+191
+111";
+
+    fn grid() -> Array2<u8> {
+        parse_array2(EXAMPLE).unwrap()
+    }
+
+    #[test]
+    fn four_connected_without_duplicates() {
+        let grid = grid();
+        let result = GraphBuilder::new(&grid, |_, to| *to as u64).build();
+        assert_eq!(result.graph.node_count(), 9);
+        // 12 neighbor pairs in a 3x3 grid, one edge per direction
+        assert_eq!(result.graph.edge_count(), 24);
+        let center = result.nodes[(1, 1)].unwrap();
+        let top = result.nodes[(0, 1)].unwrap();
+        let edge = result.graph.find_edge(top, center).unwrap();
+        assert_eq!(result.graph[edge], 9);
+        assert_eq!(result.graph[center], (1, 1));
+    }
+
+    #[test]
+    fn eight_connected_undirected() {
+        let grid = grid();
+        let result = GraphBuilder::new(&grid, |from, to| (*from, *to))
+            .connectivity(Connectivity::Eight)
+            .build_undirected();
+        // 12 horizontal/vertical and 8 diagonal pairs
+        assert_eq!(result.graph.edge_count(), 20);
+        let (a, b) = (result.nodes[(1, 1)].unwrap(), result.nodes[(0, 0)].unwrap());
+        let edge = result.graph.find_edge(a, b).unwrap();
+        assert_eq!(result.graph[edge], (1, 9));
+    }
+
+    #[test]
+    fn passable_and_custom_neighbors() {
+        let grid = grid();
+        // basins like day 9: cells of height 9 are walls
+        let result = GraphBuilder::new(&grid, |_, _| ())
+            .passable(|height| *height != 9)
+            .build_undirected();
+        assert_eq!(result.nodes[(1, 1)], None);
+        assert_eq!(result.graph.node_count(), 8);
+        assert_eq!(petgraph::algo::connected_components(&result.graph), 1);
+
+        // only moving right
+        let right = |g: &Array2<u8>, &(y, x): &Pos| {
+            (x + 1 < g.ncols())
+                .then_some((y, x + 1))
+                .into_iter()
+                .collect()
+        };
+        let result = GraphBuilder::new(&grid, |_, _| ())
+            .connectivity(Connectivity::Custom(Box::new(right)))
+            .build();
+        assert_eq!(result.graph.edge_count(), 6);
+    }
+
+    #[test]
+    fn dot_pins_grid_positions() {
+        let grid: Array2<u8> = parse_array2("12").unwrap();
+        let result = GraphBuilder::new(&grid, |_, to| *to).build_undirected();
+        assert_eq!(
+            grid_to_dot(&result.graph, |w| w.to_string()),
+            "graph {
+    0 [label=\"0,0\" pos=\"0,-0!\"]
+    1 [label=\"0,1\" pos=\"1,-0!\"]
+    0 -- 1 [label=\"2\"]
+}
+"
+        );
+    }
+}
@@ -13,22 +13,75 @@ extern crate anyhow;
 extern crate aoc_runner_derive;
 extern crate aoc_runner;
 
+pub mod answer;
+#[cfg(any(feature = "day3", feature = "day16"))]
+pub mod bits;
+pub mod cancel;
+#[cfg(feature = "day1")]
 mod day1;
+#[cfg(feature = "day10")]
 mod day10;
+#[cfg(feature = "day11")]
 mod day11;
+#[cfg(feature = "day12")]
 mod day12;
+#[cfg(feature = "day13")]
 mod day13;
+#[cfg(feature = "day14")]
 mod day14;
+#[cfg(feature = "day15")]
 mod day15;
+#[cfg(feature = "day16")]
 mod day16;
+#[cfg(feature = "day2")]
 mod day2;
+#[cfg(feature = "day3")]
 mod day3;
+#[cfg(feature = "day4")]
 mod day4;
+#[cfg(feature = "day5")]
 mod day5;
+#[cfg(feature = "day6")]
 mod day6;
+#[cfg(feature = "day7")]
 mod day7;
+#[cfg(feature = "day8")]
 mod day8;
+#[cfg(feature = "day9")]
 mod day9;
+pub mod explain;
+#[cfg(feature = "day12")]
+pub mod graph;
+#[cfg(feature = "day13")]
+pub mod ocr;
+#[cfg(feature = "day15")]
+pub mod pathfinding;
+pub mod puzzle;
+pub mod report;
+pub mod runner;
+#[cfg(any(feature = "day6", feature = "day11", feature = "day14"))]
+pub mod simulation;
+#[cfg(all(
+    test,
+    any(
+        feature = "day9",
+        feature = "day11",
+        feature = "day13",
+        feature = "day15"
+    )
+))]
+mod snapshot;
+#[cfg(feature = "day1")]
+pub mod sonar;
+#[cfg(feature = "day2")]
+pub mod submarine;
+#[cfg(any(
+    feature = "day9",
+    feature = "day11",
+    feature = "day12",
+    feature = "day15"
+))]
 mod util;
+pub mod validate;
 
 aoc_lib! { year = 2021 }
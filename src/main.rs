@@ -0,0 +1,130 @@
+use adventofcode_rust_2021::cancel::{Cancel, Cancelled};
+use adventofcode_rust_2021::report::{current_commit, to_csv, to_json, Format, Record};
+use adventofcode_rust_2021::runner::{explainer, load_input, solutions, validators, YEAR};
+use anyhow::{bail, Context};
+use std::env;
+use std::time::Duration;
+
+/// Runs all solutions or the ones selected by `-d <day>` and `-p <part>`,
+/// printing results as `--format text|json|csv`, with intermediate results if `--explain`ed.
+/// Long running parts are stopped after `--budget <seconds>`, reporting their progress instead.
+///
+/// `validate` checks the puzzle inputs of all or the selected day instead, `anomalies` lists the
+/// suspicious readings of the day 1 sonar sweep, which may miss readings as empty lines.
+fn main() -> anyhow::Result<()> {
+    let mut day: Option<u8> = None;
+    let mut part: Option<u8> = None;
+    let mut format = Format::Text;
+    let mut validate = false;
+    let mut anomaly_mode = false;
+    let mut explain = false;
+    let mut budget: Option<Duration> = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "validate" => validate = true,
+            "anomalies" => anomaly_mode = true,
+            "-e" | "--explain" => explain = true,
+            "-d" | "--day" => day = Some(args.next().context("missing day")?.parse()?),
+            "-p" | "--part" => part = Some(args.next().context("missing part")?.parse()?),
+            "-f" | "--format" => format = args.next().context("missing format")?.parse()?,
+            "-b" | "--budget" => {
+                let seconds = args.next().context("missing budget")?.parse()?;
+                budget = Some(Duration::try_from_secs_f64(seconds)?);
+            }
+            _ => bail!("unknown argument: {}", arg),
+        }
+    }
+
+    if validate {
+        return validate_inputs(day);
+    }
+    if anomaly_mode {
+        return print_anomalies();
+    }
+
+    let commit = current_commit();
+    let mut records = Vec::new();
+    if format == Format::Text {
+        println!("Advent of code {}", YEAR);
+    }
+    for solution in solutions() {
+        if day.is_some_and(|d| d != solution.day) || part.is_some_and(|p| p != solution.part) {
+            continue;
+        }
+        let input = load_input(solution.day)?;
+        let cancel = budget.map_or_else(Cancel::new, Cancel::with_budget);
+        let run = match solution.run_cancellable(&input, &cancel) {
+            Ok(run) => run,
+            Err(e) => match e.downcast_ref::<Cancelled>() {
+                Some(cancelled) => {
+                    let message = format!(
+                        "Day {} - Part {}: {}",
+                        solution.day, solution.part, cancelled
+                    );
+                    match format {
+                        Format::Text => println!("{}", message),
+                        _ => eprintln!("{}", message),
+                    }
+                    continue;
+                }
+                None => {
+                    return Err(e.context(format!("day {} part {}", solution.day, solution.part)))
+                }
+            },
+        };
+        let mut record = Record::new(&solution, &run, &input, commit.as_deref());
+        if explain {
+            if let Some(explainer) = explainer(solution.day, solution.part) {
+                record.explanation = Some(explainer.explain(&input)?);
+            }
+        }
+        if format == Format::Text {
+            println!("{}", record.to_text());
+        }
+        records.push(record);
+    }
+    match format {
+        Format::Text => {}
+        Format::Json => println!("{}", to_json(&records)?),
+        Format::Csv => print!("{}", to_csv(&records)),
+    }
+    Ok(())
+}
+
+/// prints every suspicious reading of the day 1 input
+#[cfg(feature = "day1")]
+fn print_anomalies() -> anyhow::Result<()> {
+    let found = adventofcode_rust_2021::runner::anomalies(&load_input(1)?)?;
+    for anomaly in &found {
+        let depth = anomaly.depth.map_or_else(|| "-".into(), |d| d.to_string());
+        println!("reading {} ({}): {}", anomaly.index, depth, anomaly.reason);
+    }
+    println!("{} anomalies found", found.len());
+    Ok(())
+}
+
+#[cfg(not(feature = "day1"))]
+fn print_anomalies() -> anyhow::Result<()> {
+    bail!("day 1 is not compiled")
+}
+
+/// prints every violated invariant of the puzzle inputs, failing if there is any
+fn validate_inputs(day: Option<u8>) -> anyhow::Result<()> {
+    let mut count = 0;
+    for validator in validators() {
+        if day.is_some_and(|d| d != validator.day) {
+            continue;
+        }
+        let input = load_input(validator.day)?;
+        for violation in validator.check(&input) {
+            println!("day {}: {}", validator.day, violation);
+            count += 1;
+        }
+    }
+    if count > 0 {
+        bail!("{} violations found", count);
+    }
+    println!("all inputs valid");
+    Ok(())
+}
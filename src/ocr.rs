@@ -0,0 +1,240 @@
+//! Reading the block letters some puzzles draw as their answer
+//!
+//! Advent of Code uses two fonts: letters 4 columns wide and 6 rows high, and letters 6 columns
+//! wide and 10 rows high. Letters are separated by at least one empty column, so every run of
+//! non-empty columns is one glyph. Glyphs are stored without surrounding empty columns, which
+//! makes narrow letters like `I` match regardless of how they are aligned in their cell.
+
+use ndarray::{Array2, Axis};
+
+/// Letters of one size, drawn with `#` and `.`
+struct Font {
+    height: usize,
+    glyphs: &'static [(char, &'static str)],
+}
+
+const SMALL: Font = Font {
+    height: 6,
+    glyphs: &[
+        ('A', ".##.\n#..#\n#..#\n####\n#..#\n#..#"),
+        ('B', "###.\n#..#\n###.\n#..#\n#..#\n###."),
+        ('C', ".##.\n#..#\n#...\n#...\n#..#\n.##."),
+        ('E', "####\n#...\n###.\n#...\n#...\n####"),
+        ('F', "####\n#...\n###.\n#...\n#...\n#..."),
+        ('G', ".##.\n#..#\n#...\n#.##\n#..#\n.###"),
+        ('H', "#..#\n#..#\n####\n#..#\n#..#\n#..#"),
+        ('I', "###\n.#.\n.#.\n.#.\n.#.\n###"),
+        ('J', "..##\n...#\n...#\n...#\n#..#\n.##."),
+        ('K', "#..#\n#.#.\n##..\n#.#.\n#.#.\n#..#"),
+        ('L', "#...\n#...\n#...\n#...\n#...\n####"),
+        ('O', ".##.\n#..#\n#..#\n#..#\n#..#\n.##."),
+        ('P', "###.\n#..#\n#..#\n###.\n#...\n#..."),
+        ('R', "###.\n#..#\n#..#\n###.\n#.#.\n#..#"),
+        ('S', ".###\n#...\n#...\n.##.\n...#\n###."),
+        ('U', "#..#\n#..#\n#..#\n#..#\n#..#\n.##."),
+        ('Y', "#...#\n#...#\n.#.#.\n..#..\n..#..\n..#.."),
+        ('Z', "####\n...#\n..#.\n.#..\n#...\n####"),
+    ],
+};
+
+const LARGE: Font = Font {
+    height: 10,
+    glyphs: &[
+        (
+            'A',
+            "..##..\n.#..#.\n#....#\n#....#\n#....#\n######\n#....#\n#....#\n#....#\n#....#",
+        ),
+        (
+            'B',
+            "#####.\n#....#\n#....#\n#....#\n#####.\n#....#\n#....#\n#....#\n#....#\n#####.",
+        ),
+        (
+            'C',
+            ".####.\n#....#\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#....#\n.####.",
+        ),
+        (
+            'E',
+            "######\n#.....\n#.....\n#.....\n#####.\n#.....\n#.....\n#.....\n#.....\n######",
+        ),
+        (
+            'F',
+            "######\n#.....\n#.....\n#.....\n#####.\n#.....\n#.....\n#.....\n#.....\n#.....",
+        ),
+        (
+            'G',
+            ".####.\n#....#\n#.....\n#.....\n#.....\n#..###\n#....#\n#....#\n#...##\n.###.#",
+        ),
+        (
+            'H',
+            "#....#\n#....#\n#....#\n#....#\n######\n#....#\n#....#\n#....#\n#....#\n#....#",
+        ),
+        (
+            'J',
+            "...###\n....#.\n....#.\n....#.\n....#.\n....#.\n....#.\n#...#.\n#...#.\n.###..",
+        ),
+        (
+            'K',
+            "#....#\n#...#.\n#..#..\n#.#...\n##....\n##....\n#.#...\n#..#..\n#...#.\n#....#",
+        ),
+        (
+            'L',
+            "#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n######",
+        ),
+        (
+            'N',
+            "#....#\n##...#\n##...#\n#.#..#\n#.#..#\n#..#.#\n#..#.#\n#...##\n#...##\n#....#",
+        ),
+        (
+            'P',
+            "#####.\n#....#\n#....#\n#....#\n#####.\n#.....\n#.....\n#.....\n#.....\n#.....",
+        ),
+        (
+            'R',
+            "#####.\n#....#\n#....#\n#....#\n#####.\n#..#..\n#...#.\n#...#.\n#....#\n#....#",
+        ),
+        (
+            'X',
+            "#....#\n#....#\n.#..#.\n.#..#.\n..##..\n..##..\n.#..#.\n.#..#.\n#....#\n#....#",
+        ),
+        (
+            'Z',
+            "######\n.....#\n.....#\n....#.\n...#..\n..#...\n.#....\n#.....\n#.....\n######",
+        ),
+    ],
+};
+
+/// reads the letters drawn by the `true` cells of a grid
+///
+/// Empty rows around the letters are ignored, their height selects the font. Fails on glyphs
+/// which are not part of the font, showing where they are and what they look like.
+pub fn read(grid: &Array2<bool>) -> anyhow::Result<String> {
+    let lit_rows: Vec<usize> = grid
+        .axis_iter(Axis(0))
+        .enumerate()
+        .filter(|(_, row)| row.iter().any(|&lit| lit))
+        .map(|(y, _)| y)
+        .collect();
+    let (top, bottom) = match (lit_rows.first(), lit_rows.last()) {
+        (Some(&top), Some(&bottom)) => (top, bottom),
+        _ => bail!("no letters found"),
+    };
+    let height = bottom - top + 1;
+    let font = match [SMALL, LARGE].into_iter().find(|f| f.height == height) {
+        Some(font) => font,
+        None => bail!(
+            "letters are {} rows high, only fonts with {} and {} rows are known",
+            height,
+            SMALL.height,
+            LARGE.height
+        ),
+    };
+
+    let lit_column = |x: usize| (top..=bottom).any(|y| grid[(y, x)]);
+    let mut text = String::new();
+    let mut x = 0;
+    while x < grid.ncols() {
+        if !lit_column(x) {
+            x += 1;
+            continue;
+        }
+        let start = x;
+        while x < grid.ncols() && lit_column(x) {
+            x += 1;
+        }
+        let glyph = (top..=bottom)
+            .map(|y| {
+                (start..x)
+                    .map(|x| if grid[(y, x)] { '#' } else { '.' })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        match font.glyphs.iter().find(|(_, g)| *g == glyph) {
+            Some((letter, _)) => text.push(*letter),
+            None => bail!("unrecognised glyph at column {}:\n{}", start, glyph),
+        }
+    }
+    Ok(text)
+}
+
+/// reads the letters of an image drawn with `#` for lit cells, like the puzzle descriptions do
+pub fn read_str(image: &str) -> anyhow::Result<String> {
+    let lines: Vec<&str> = image.lines().collect();
+    let width = lines.iter().map(|line| line.chars().count()).max();
+    let mut grid = Array2::from_elem((lines.len(), width.unwrap_or_default()), false);
+    for (y, line) in lines.iter().enumerate() {
+        for (x, c) in line.chars().enumerate() {
+            grid[(y, x)] = c == '#';
+        }
+    }
+    read(&grid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn small_letters() {
+        let image = "
+#..#.####.#....#.....##.
+#..#.#....#....#....#..#
+####.###..#....#....#..#
+#..#.#....#....#....#..#
+#..#.#....#....#....#..#
+#..#.####.####.####..##.
+";
+        assert_eq!(read_str(image).unwrap(), "HELLO");
+        // narrow letters and wider gaps
+        let image = "###...#...#...#
+.#....#...#...#
+.#.....#.#....#
+.#......#.....#
+.#......#.....#
+###.....#.....####";
+        assert_eq!(read_str(image).unwrap(), "IYL");
+    }
+
+    #[test]
+    fn large_letters() {
+        let image = "#....#..######
+#....#.......#
+#....#.......#
+#....#......#.
+######.....#..
+#....#....#...
+#....#...#....
+#....#..#.....
+#....#..#.....
+#....#..######";
+        assert_eq!(read_str(image).unwrap(), "HZ");
+    }
+
+    #[test]
+    fn unreadable_images() {
+        assert_eq!(
+            read_str("....\n....").unwrap_err().to_string(),
+            "no letters found"
+        );
+        assert_eq!(
+            read_str("#\n#\n#").unwrap_err().to_string(),
+            "letters are 3 rows high, only fonts with 6 and 10 rows are known"
+        );
+        let image = ".##..#####
+#..#.#...#
+#..#.#...#
+#..#.#...#
+#..#.#...#
+.##..#####";
+        assert_eq!(
+            read_str(image).unwrap_err().to_string(),
+            "unrecognised glyph at column 5:
+#####
+#...#
+#...#
+#...#
+#...#
+#####"
+        );
+    }
+}
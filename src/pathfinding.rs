@@ -0,0 +1,283 @@
+//! Shortest paths on grids, without building a graph first
+//!
+//! Positions are `(row, column)` tuples like the indices of `Array2`. Every search takes
+//! - `neighbors`: the positions reachable from a position, e.g. `util::get_neighbors4`
+//! - `cost`: the cost of stepping from one position to a neighbor, `None` if impassable
+
+use crate::cancel::{Cancel, Cancelled};
+use ndarray::Array2;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+pub type Pos = (usize, usize);
+
+/// Cheapest path found by a search, including start and goal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path {
+    pub cost: u64,
+    pub positions: Vec<Pos>,
+}
+
+/// Dijkstra's algorithm
+pub fn dijkstra<T, N, C>(
+    grid: &Array2<T>,
+    start: Pos,
+    goal: Pos,
+    neighbors: N,
+    cost: C,
+) -> Option<Path>
+where
+    N: Fn(&Array2<T>, &Pos) -> Vec<Pos>,
+    C: Fn(Pos, Pos) -> Option<u64>,
+{
+    astar_with(grid, start, goal, neighbors, cost, |_| 0)
+}
+
+/// A* with the manhattan distance to the goal as heuristic
+///
+/// The heuristic only gives the cheapest path on 4-connected neighbors where every step costs at
+/// least `1`, use `astar_with` otherwise.
+pub fn astar<T, N, C>(
+    grid: &Array2<T>,
+    start: Pos,
+    goal: Pos,
+    neighbors: N,
+    cost: C,
+) -> Option<Path>
+where
+    N: Fn(&Array2<T>, &Pos) -> Vec<Pos>,
+    C: Fn(Pos, Pos) -> Option<u64>,
+{
+    astar_with(grid, start, goal, neighbors, cost, |pos| {
+        manhattan(pos, goal)
+    })
+}
+
+/// A* with a custom heuristic, which must never overestimate the remaining cost
+pub fn astar_with<T, N, C, H>(
+    grid: &Array2<T>,
+    start: Pos,
+    goal: Pos,
+    neighbors: N,
+    cost: C,
+    heuristic: H,
+) -> Option<Path>
+where
+    N: Fn(&Array2<T>, &Pos) -> Vec<Pos>,
+    C: Fn(Pos, Pos) -> Option<u64>,
+    H: Fn(Pos) -> u64,
+{
+    let mut search = Search::new(grid, start);
+    let mut queue = BinaryHeap::new();
+    queue.push(Reverse((heuristic(start), start)));
+    while let Some(Reverse((_, pos))) = queue.pop() {
+        if pos == goal {
+            return Some(search.path(goal));
+        }
+        if !search.visit(pos) {
+            continue;
+        }
+        for next in neighbors(grid, &pos) {
+            if let Some(distance) = search.relax(pos, next, cost(pos, next)) {
+                queue.push(Reverse((distance + heuristic(next), next)));
+            }
+        }
+    }
+    None
+}
+
+/// Dial's algorithm, Dijkstra with a bucket per distance instead of a binary heap
+///
+/// Faster than `dijkstra` for small integer costs like the digits of puzzle grids.
+pub fn dial<T, N, C>(grid: &Array2<T>, start: Pos, goal: Pos, neighbors: N, cost: C) -> Option<Path>
+where
+    N: Fn(&Array2<T>, &Pos) -> Vec<Pos>,
+    C: Fn(Pos, Pos) -> Option<u64>,
+{
+    dial_cancellable(grid, start, goal, neighbors, cost, &Cancel::new()).expect("never cancelled")
+}
+
+/// number of positions visited between two checks for cancellation
+const CANCEL_INTERVAL: usize = 1024;
+
+/// like `dial`, stopping with the number of positions visited so far once cancelled
+pub fn dial_cancellable<T, N, C>(
+    grid: &Array2<T>,
+    start: Pos,
+    goal: Pos,
+    neighbors: N,
+    cost: C,
+    cancel: &Cancel,
+) -> Result<Option<Path>, Cancelled>
+where
+    N: Fn(&Array2<T>, &Pos) -> Vec<Pos>,
+    C: Fn(Pos, Pos) -> Option<u64>,
+{
+    let mut search = Search::new(grid, start);
+    let mut visits = 0;
+    let mut buckets: Vec<Vec<Pos>> = vec![vec![start]];
+    let mut distance = 0;
+    while distance < buckets.len() {
+        while let Some(pos) = buckets[distance].pop() {
+            if pos == goal {
+                return Ok(Some(search.path(goal)));
+            }
+            if !search.visit(pos) {
+                continue;
+            }
+            visits += 1;
+            if visits % CANCEL_INTERVAL == 0 {
+                cancel.check(|| format!("{} of {} positions visited", visits, grid.len()))?;
+            }
+            for next in neighbors(grid, &pos) {
+                if let Some(next_distance) = search.relax(pos, next, cost(pos, next)) {
+                    let next_distance = next_distance as usize;
+                    if next_distance >= buckets.len() {
+                        buckets.resize(next_distance + 1, Vec::new());
+                    }
+                    buckets[next_distance].push(next);
+                }
+            }
+        }
+        distance += 1;
+    }
+    Ok(None)
+}
+
+/// manhattan distance of two positions
+pub fn manhattan(a: Pos, b: Pos) -> u64 {
+    (a.0.abs_diff(b.0) + a.1.abs_diff(b.1)) as u64
+}
+
+/// Distances and predecessors shared by all searches
+struct Search {
+    distances: Array2<u64>,
+    previous: Array2<Option<Pos>>,
+    visited: Array2<bool>,
+}
+
+impl Search {
+    fn new<T>(grid: &Array2<T>, start: Pos) -> Search {
+        let shape = grid.raw_dim();
+        let mut distances = Array2::from_elem(shape, u64::MAX);
+        distances[start] = 0;
+        Search {
+            distances,
+            previous: Array2::from_elem(shape, None),
+            visited: Array2::from_elem(shape, false),
+        }
+    }
+
+    /// marks pos as visited, false if it was visited before
+    fn visit(&mut self, pos: Pos) -> bool {
+        !std::mem::replace(&mut self.visited[pos], true)
+    }
+
+    /// the new distance of `to` if going there from `from` is shorter than known so far
+    fn relax(&mut self, from: Pos, to: Pos, cost: Option<u64>) -> Option<u64> {
+        let distance = self.distances[from].checked_add(cost?)?;
+        if distance >= self.distances[to] {
+            return None;
+        }
+        self.distances[to] = distance;
+        self.previous[to] = Some(from);
+        Some(distance)
+    }
+
+    fn path(&self, goal: Pos) -> Path {
+        let mut positions = vec![goal];
+        while let Some(pos) = self.previous[*positions.last().unwrap()] {
+            positions.push(pos);
+        }
+        positions.reverse();
+        Path {
+            cost: self.distances[goal],
+            positions,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::{get_neighbors4, get_neighbors8, parse_array2};
+
+    const EXAMPLE: &str = "131
+191
+111";
+
+    type Searcher = fn(
+        &Array2<u64>,
+        Pos,
+        Pos,
+        fn(&Array2<u64>, &Pos) -> Vec<Pos>,
+        &dyn Fn(Pos, Pos) -> Option<u64>,
+    ) -> Option<Path>;
+
+    const SEARCHES: [Searcher; 3] = [
+        |g, s, e, n, c| dijkstra(g, s, e, n, c),
+        |g, s, e, n, c| astar(g, s, e, n, c),
+        |g, s, e, n, c| dial(g, s, e, n, c),
+    ];
+
+    #[test]
+    fn cheapest_path_around_wall() {
+        let grid: Array2<u64> = parse_array2(EXAMPLE).unwrap();
+        for search in SEARCHES {
+            let path = search(&grid, (0, 0), (2, 2), get_neighbors4, &|_, to| {
+                Some(grid[to])
+            })
+            .unwrap();
+            assert_eq!(path.cost, 4);
+            assert_eq!(path.positions, [(0, 0), (1, 0), (2, 0), (2, 1), (2, 2)]);
+        }
+    }
+
+    #[test]
+    fn diagonal_neighbors() {
+        let grid: Array2<u64> = parse_array2(EXAMPLE).unwrap();
+        let cost = |_, to: Pos| Some(grid[to]);
+        let chebyshev = |(y, x): Pos| y.abs_diff(2).max(x.abs_diff(2)) as u64;
+        let paths = [
+            dijkstra(&grid, (0, 0), (2, 2), get_neighbors8, cost),
+            astar_with(&grid, (0, 0), (2, 2), get_neighbors8, cost, chebyshev),
+            dial(&grid, (0, 0), (2, 2), get_neighbors8, cost),
+        ];
+        for path in paths {
+            let path = path.unwrap();
+            assert_eq!(path.cost, 3);
+            assert_eq!(path.positions, [(0, 0), (1, 0), (2, 1), (2, 2)]);
+        }
+    }
+
+    #[test]
+    fn impassable_cells() {
+        let grid: Array2<u64> = parse_array2(EXAMPLE).unwrap();
+        let walls = |_, to: Pos| (grid[to] != 9).then_some(1);
+        let blocked = |_, to: Pos| (to != (1, 0) && to != (0, 1)).then_some(1);
+        for search in SEARCHES {
+            assert_eq!(search(&grid, (0, 0), (1, 1), get_neighbors4, &walls), None);
+            assert_eq!(
+                search(&grid, (0, 0), (2, 2), get_neighbors4, &blocked),
+                None
+            );
+            let path = search(&grid, (0, 0), (0, 0), get_neighbors4, &walls).unwrap();
+            assert_eq!((path.cost, path.positions), (0, vec![(0, 0)]));
+        }
+    }
+
+    #[test]
+    fn cancelled_search() {
+        let grid: Array2<u64> = Array2::from_elem((64, 64), 1);
+        let cost = |_, _| Some(1);
+        let cancel = Cancel::new();
+        let path = dial_cancellable(&grid, (0, 0), (63, 63), get_neighbors4, cost, &cancel);
+        assert_eq!(path.unwrap().unwrap().cost, 126);
+        cancel.cancel();
+        let cancelled = dial_cancellable(&grid, (0, 0), (63, 63), get_neighbors4, cost, &cancel);
+        assert_eq!(
+            cancelled.unwrap_err().progress,
+            "1024 of 4096 positions visited"
+        );
+    }
+}
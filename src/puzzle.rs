@@ -0,0 +1,111 @@
+/// Puzzle text of a compiled day, taken from the `//!` docs of its module
+pub struct Puzzle {
+    pub day: u8,
+    source: &'static str,
+}
+
+impl Puzzle {
+    /// title of the puzzle like `Sonar Sweep`, without the day prefix and link
+    pub fn title(&self) -> String {
+        let first = self.doc_lines().next().unwrap_or_default();
+        let heading = first.trim_start_matches('#').trim();
+        let heading = match (heading.find('['), heading.find(']')) {
+            (Some(start), Some(end)) if start < end => &heading[start + 1..end],
+            _ => heading,
+        };
+        match heading.split_once(": ") {
+            Some((_, title)) => title.to_string(),
+            None => heading.to_string(),
+        }
+    }
+
+    /// full puzzle description as markdown
+    pub fn description(&self) -> String {
+        self.doc_lines().collect::<Vec<_>>().join("\n")
+    }
+
+    fn doc_lines(&self) -> impl Iterator<Item = &'static str> {
+        self.source
+            .lines()
+            .map_while(|line| line.strip_prefix("//!"))
+            .map(|line| line.strip_prefix(' ').unwrap_or(line))
+    }
+}
+
+/// registers the module source of a day
+#[allow(unused_macros)] // when no day feature is enabled
+macro_rules! puzzle {
+    ($day:literal, $nr:literal) => {
+        Puzzle {
+            day: $nr,
+            source: include_str!(concat!($day, ".rs")),
+        }
+    };
+}
+
+/// puzzles of all compiled days with solutions, ordered by day
+#[allow(clippy::vec_init_then_push)] // with only a single day compiled
+pub fn puzzles() -> Vec<Puzzle> {
+    #[allow(unused_mut)]
+    let mut puzzles = Vec::new();
+    #[cfg(feature = "day1")]
+    puzzles.push(puzzle!("day1", 1));
+    #[cfg(feature = "day2")]
+    puzzles.push(puzzle!("day2", 2));
+    #[cfg(feature = "day3")]
+    puzzles.push(puzzle!("day3", 3));
+    #[cfg(feature = "day4")]
+    puzzles.push(puzzle!("day4", 4));
+    #[cfg(feature = "day5")]
+    puzzles.push(puzzle!("day5", 5));
+    #[cfg(feature = "day6")]
+    puzzles.push(puzzle!("day6", 6));
+    #[cfg(feature = "day7")]
+    puzzles.push(puzzle!("day7", 7));
+    #[cfg(feature = "day8")]
+    puzzles.push(puzzle!("day8", 8));
+    #[cfg(feature = "day9")]
+    puzzles.push(puzzle!("day9", 9));
+    #[cfg(feature = "day10")]
+    puzzles.push(puzzle!("day10", 10));
+    #[cfg(feature = "day11")]
+    puzzles.push(puzzle!("day11", 11));
+    #[cfg(feature = "day12")]
+    puzzles.push(puzzle!("day12", 12));
+    #[cfg(feature = "day13")]
+    puzzles.push(puzzle!("day13", 13));
+    #[cfg(feature = "day14")]
+    puzzles.push(puzzle!("day14", 14));
+    #[cfg(feature = "day15")]
+    puzzles.push(puzzle!("day15", 15));
+    puzzles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn title_variants() {
+        let titles = [
+            "//! # [Day 1: Sonar Sweep](https://adventofcode.com/2021/day/1)\n//!\n//! text",
+            "//! [Day 13: Transparent Origami](https://adventofcode.com/2021/day/13)",
+            "//! [Day 15: Chiton]()",
+            "//! Untitled",
+        ]
+        .map(|source| Puzzle { day: 1, source }.title());
+        assert_eq!(
+            titles,
+            ["Sonar Sweep", "Transparent Origami", "Chiton", "Untitled"]
+        );
+    }
+
+    #[test]
+    fn description_strips_doc_prefix() {
+        let puzzle = Puzzle {
+            day: 1,
+            source: "//! # Title\n//!\n//! ```plain\n//!  indented\n//! ```\n\nuse std;",
+        };
+        assert_eq!(puzzle.description(), "# Title\n\n```plain\n indented\n```");
+    }
+}
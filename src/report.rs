@@ -0,0 +1,189 @@
+use crate::answer::Answer;
+use crate::explain::Explanation;
+use crate::runner::{Run, Solution, YEAR};
+use serde::Serialize;
+use std::process::Command;
+use std::str::FromStr;
+
+/// Output formats supported by the runner
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    Json,
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            _ => Err(anyhow!("unknown format: {}", s)),
+        }
+    }
+}
+
+/// Machine readable result of running a single part
+#[derive(Debug, Clone, Serialize)]
+pub struct Record {
+    pub year: u32,
+    pub day: u8,
+    pub part: u8,
+    pub answer: Answer,
+    pub generator_ns: u64,
+    pub runner_ns: u64,
+    pub input_hash: String,
+    pub commit: Option<String>,
+    /// intermediate results, only present when asked for with `--explain`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<Explanation>,
+}
+
+impl Record {
+    pub fn new(solution: &Solution, run: &Run, input: &str, commit: Option<&str>) -> Record {
+        Record {
+            year: YEAR,
+            day: solution.day,
+            part: solution.part,
+            answer: run.answer.clone(),
+            generator_ns: run.generator.as_nanos() as u64,
+            runner_ns: run.runner.as_nanos() as u64,
+            input_hash: input_hash(input),
+            commit: commit.map(|c| c.to_string()),
+            explanation: None,
+        }
+    }
+
+    /// human readable output in the same layout as cargo-aoc, followed by the explanation
+    pub fn to_text(&self) -> String {
+        let mut text = format!(
+            "Day {} - Part {}: {}\n\tgenerator: {:?},\n\trunner: {:?}\n",
+            self.day,
+            self.part,
+            self.answer,
+            std::time::Duration::from_nanos(self.generator_ns),
+            std::time::Duration::from_nanos(self.runner_ns)
+        );
+        if let Some(explanation) = &self.explanation {
+            for line in explanation.to_string().lines() {
+                text += &format!("\t{}\n", line);
+            }
+        }
+        text
+    }
+}
+
+/// renders records as pretty printed JSON array
+pub fn to_json(records: &[Record]) -> anyhow::Result<String> {
+    Ok(serde_json::to_string_pretty(records)?)
+}
+
+/// renders records as CSV including a header line
+pub fn to_csv(records: &[Record]) -> String {
+    let mut out = String::from("year,day,part,answer,generator_ns,runner_ns,input_hash,commit\n");
+    for record in records {
+        let answer = match &record.answer {
+            Answer::Grid(grid) => grid.clone(),
+            answer => answer.to_string(),
+        };
+        out += &format!(
+            "{},{},{},{},{},{},{},{}\n",
+            record.year,
+            record.day,
+            record.part,
+            csv_field(&answer),
+            record.generator_ns,
+            record.runner_ns,
+            record.input_hash,
+            record.commit.as_deref().unwrap_or("")
+        );
+    }
+    out
+}
+
+/// quotes a CSV field if it contains separators, quotes or newlines
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// stable 64 bit FNV-1a hash of the puzzle input as hex string
+///
+/// The std `DefaultHasher` is not guaranteed to be stable between Rust releases,
+/// which would make hashes of different runs incomparable.
+pub fn input_hash(input: &str) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in input.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{:016x}", hash)
+}
+
+/// commit of the checked out source, taken from `GITHUB_SHA` or asked from git
+pub fn current_commit() -> Option<String> {
+    if let Ok(sha) = std::env::var("GITHUB_SHA") {
+        return Some(sha);
+    }
+    let output = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(answer: Answer) -> Record {
+        Record {
+            year: 2021,
+            day: 13,
+            part: 2,
+            answer,
+            generator_ns: 10,
+            runner_ns: 20,
+            input_hash: input_hash(""),
+            commit: None,
+            explanation: None,
+        }
+    }
+
+    #[test]
+    fn hash_is_stable() {
+        assert_eq!(input_hash(""), "cbf29ce484222325");
+        assert_eq!(input_hash("a"), "af63dc4c8601ec8c");
+    }
+
+    #[test]
+    fn csv_quotes_grids() {
+        let csv = to_csv(&[record(Answer::Grid("#.\n.#".into()))]);
+        assert_eq!(csv.lines().nth(1), Some("2021,13,2,\"#."));
+        assert!(csv.ends_with(".#\",10,20,cbf29ce484222325,\n"));
+    }
+
+    #[test]
+    fn json_keeps_numbers() {
+        let json = to_json(&[record(Answer::Int(17))]).unwrap();
+        assert!(json.contains("\"answer\": 17"));
+        assert!(json.contains("\"commit\": null"));
+        assert!(!json.contains("explanation"));
+    }
+
+    #[test]
+    fn text_includes_explanation() {
+        let mut record = record(Answer::Int(17));
+        record.explanation = Some(Explanation::new().value("gamma", 22));
+        assert!(record.to_text().ends_with("\n\tgamma: 22\n"));
+    }
+}
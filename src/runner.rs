@@ -0,0 +1,513 @@
+use crate::answer::Answer;
+use crate::cancel::Cancel;
+use crate::explain::Explanation;
+#[cfg(feature = "day1")]
+use crate::sonar::Anomaly;
+use crate::validate::Violation;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Year all solutions of this crate belong to
+pub const YEAR: u32 = 2021;
+
+/// A single registered puzzle part
+pub struct Solution {
+    pub day: u8,
+    pub part: u8,
+    solve: fn(&str, &Cancel) -> anyhow::Result<Run>,
+}
+
+/// Answer of a solution together with the time spent parsing and solving
+#[derive(Debug, Clone)]
+pub struct Run {
+    pub answer: Answer,
+    pub generator: Duration,
+    pub runner: Duration,
+}
+
+impl Solution {
+    /// runs generator and solver of this part on given puzzle input
+    pub fn run(&self, input: &str) -> anyhow::Result<Run> {
+        self.run_cancellable(input, &Cancel::new())
+    }
+
+    /// runs this part, stopping with a `Cancelled` error once the token is cancelled
+    ///
+    /// Only the long running parts check the token, all others run until they are done.
+    pub fn run_cancellable(&self, input: &str, cancel: &Cancel) -> anyhow::Result<Run> {
+        (self.solve)(input, cancel)
+    }
+}
+
+/// A registered input generator, used to parse inputs without solving them
+pub struct Generator {
+    pub day: u8,
+    parse: fn(&str) -> anyhow::Result<()>,
+}
+
+impl Generator {
+    /// parses given puzzle input, discarding the result
+    pub fn parse(&self, input: &str) -> anyhow::Result<()> {
+        (self.parse)(input)
+    }
+}
+
+/// A registered input validator, checking the puzzle invariants a day relies on
+pub struct Validator {
+    pub day: u8,
+    check: fn(&str) -> Vec<Violation>,
+}
+
+impl Validator {
+    /// all violated invariants of given puzzle input, empty if the input is valid
+    pub fn check(&self, input: &str) -> Vec<Violation> {
+        (self.check)(input)
+    }
+}
+
+/// A registered explanation of a part, listing the intermediate results behind its answer
+pub struct Explainer {
+    pub day: u8,
+    pub part: u8,
+    explain: fn(&str) -> anyhow::Result<Explanation>,
+}
+
+impl Explainer {
+    /// runs generator and explanation of this part on given puzzle input
+    pub fn explain(&self, input: &str) -> anyhow::Result<Explanation> {
+        (self.explain)(input)
+    }
+}
+
+/// Rendered states of a simulating day, one frame per step
+pub struct Animation {
+    pub day: u8,
+    frames: fn(&str) -> anyhow::Result<Vec<String>>,
+}
+
+impl Animation {
+    /// runs generator and simulation of this day on given puzzle input
+    pub fn frames(&self, input: &str) -> anyhow::Result<Vec<String>> {
+        (self.frames)(input)
+    }
+}
+
+/// registers a day part by calling its generator (if any) and its solver, which is passed the
+/// cancellation token if it is `cancellable`
+#[allow(unused_macros)] // when no day feature is enabled
+macro_rules! solution {
+    ($day:ident, $nr:literal, $part:ident, $part_nr:literal) => {
+        Solution {
+            day: $nr,
+            part: $part_nr,
+            solve: |input, _| {
+                let start = std::time::Instant::now();
+                let generated = crate::$day::parse_input(input)?;
+                let inter = std::time::Instant::now();
+                let answer = crate::$day::$part(&generated);
+                Ok(Run {
+                    answer,
+                    generator: inter - start,
+                    runner: inter.elapsed(),
+                })
+            },
+        }
+    };
+    ($day:ident, $nr:literal, $part:ident, $part_nr:literal, fallible) => {
+        Solution {
+            day: $nr,
+            part: $part_nr,
+            solve: |input, _| {
+                let start = std::time::Instant::now();
+                let generated = crate::$day::parse_input(input)?;
+                let inter = std::time::Instant::now();
+                let answer = crate::$day::$part(&generated)?;
+                Ok(Run {
+                    answer,
+                    generator: inter - start,
+                    runner: inter.elapsed(),
+                })
+            },
+        }
+    };
+    ($day:ident, $nr:literal, $part:ident, $part_nr:literal, cancellable) => {
+        Solution {
+            day: $nr,
+            part: $part_nr,
+            solve: |input, cancel| {
+                let start = std::time::Instant::now();
+                let generated = crate::$day::parse_input(input)?;
+                let inter = std::time::Instant::now();
+                let answer = crate::$day::$part(&generated, cancel)?;
+                Ok(Run {
+                    answer,
+                    generator: inter - start,
+                    runner: inter.elapsed(),
+                })
+            },
+        }
+    };
+    ($day:ident, $nr:literal, $part:ident, $part_nr:literal, raw) => {
+        Solution {
+            day: $nr,
+            part: $part_nr,
+            solve: |input, _| {
+                let start = std::time::Instant::now();
+                let answer = crate::$day::$part(input);
+                Ok(Run {
+                    answer,
+                    generator: Duration::ZERO,
+                    runner: start.elapsed(),
+                })
+            },
+        }
+    };
+    ($day:ident, $nr:literal, $part:ident, $part_nr:literal, raw, fallible) => {
+        Solution {
+            day: $nr,
+            part: $part_nr,
+            solve: |input, _| {
+                let start = std::time::Instant::now();
+                let answer = crate::$day::$part(input)?;
+                Ok(Run {
+                    answer,
+                    generator: Duration::ZERO,
+                    runner: start.elapsed(),
+                })
+            },
+        }
+    };
+}
+
+/// registers the generator of a day
+#[allow(unused_macros)] // when no day feature is enabled
+macro_rules! generator {
+    ($day:ident, $nr:literal) => {
+        Generator {
+            day: $nr,
+            parse: |input| crate::$day::parse_input(input).map(|_| ()),
+        }
+    };
+}
+
+/// registers the explanation of a day part by calling its generator and explain function
+#[allow(unused_macros)] // when no day feature is enabled
+macro_rules! explainer {
+    ($day:ident, $nr:literal, $explain:ident, $part_nr:literal) => {
+        Explainer {
+            day: $nr,
+            part: $part_nr,
+            explain: |input| Ok(crate::$day::$explain(&crate::$day::parse_input(input)?)),
+        }
+    };
+    ($day:ident, $nr:literal, $explain:ident, $part_nr:literal, fallible) => {
+        Explainer {
+            day: $nr,
+            part: $part_nr,
+            explain: |input| crate::$day::$explain(&crate::$day::parse_input(input)?),
+        }
+    };
+}
+
+/// registers the validator of a day, falling back to a failing generator as only violation
+#[allow(unused_macros)] // when no day feature is enabled
+macro_rules! validator {
+    ($day:ident, $nr:literal) => {
+        Validator {
+            day: $nr,
+            check: |input| crate::validate::parse_error(crate::$day::parse_input(input)),
+        }
+    };
+    ($day:ident, $nr:literal, $check:path) => {
+        Validator {
+            day: $nr,
+            check: $check,
+        }
+    };
+}
+
+/// all implemented puzzle parts of the compiled days, ordered by day and part
+///
+/// Day 16 is left out until its puzzle input is checked in, as `tests/answers.rs` runs every
+/// registered part against the real input. `cargo aoc` still runs it.
+pub fn solutions() -> Vec<Solution> {
+    #[allow(unused_mut)]
+    let mut solutions = Vec::new();
+    #[cfg(feature = "day1")]
+    solutions.extend([solution!(day1, 1, part1, 1), solution!(day1, 1, part2, 2)]);
+    #[cfg(feature = "day2")]
+    solutions.extend([
+        solution!(day2, 2, part1, 1, fallible),
+        solution!(day2, 2, part2, 2, fallible),
+    ]);
+    #[cfg(feature = "day3")]
+    solutions.extend([solution!(day3, 3, part1, 1), solution!(day3, 3, part2, 2)]);
+    #[cfg(feature = "day4")]
+    solutions.extend([
+        solution!(day4, 4, part1, 1, fallible),
+        solution!(day4, 4, part2, 2, fallible),
+    ]);
+    #[cfg(feature = "day5")]
+    solutions.extend([solution!(day5, 5, part1, 1), solution!(day5, 5, part2, 2)]);
+    #[cfg(feature = "day6")]
+    solutions.extend([solution!(day6, 6, part1, 1), solution!(day6, 6, part2, 2)]);
+    #[cfg(feature = "day7")]
+    solutions.extend([solution!(day7, 7, part1, 1), solution!(day7, 7, part2, 2)]);
+    #[cfg(feature = "day8")]
+    solutions.extend([
+        solution!(day8, 8, part1, 1),
+        solution!(day8, 8, part2, 2, fallible),
+    ]);
+    #[cfg(feature = "day9")]
+    solutions.extend([
+        solution!(day9, 9, part1, 1),
+        solution!(day9, 9, part2, 2, fallible),
+    ]);
+    #[cfg(feature = "day10")]
+    solutions.extend([
+        solution!(day10, 10, part1, 1, raw),
+        solution!(day10, 10, part2, 2, raw, fallible),
+    ]);
+    #[cfg(feature = "day11")]
+    solutions.extend([
+        solution!(day11, 11, part1, 1),
+        solution!(day11, 11, part2_cancellable, 2, cancellable),
+    ]);
+    #[cfg(feature = "day12")]
+    solutions.push(solution!(day12, 12, part1_cancellable, 1, cancellable));
+    #[cfg(feature = "day13")]
+    solutions.extend([
+        solution!(day13, 13, part1, 1),
+        solution!(day13, 13, part2, 2),
+    ]);
+    #[cfg(feature = "day14")]
+    solutions.extend([
+        solution!(day14, 14, part1, 1),
+        solution!(day14, 14, part2, 2),
+    ]);
+    #[cfg(feature = "day15")]
+    solutions.extend([
+        solution!(day15, 15, part1_cancellable, 1, cancellable),
+        solution!(day15, 15, part2_cancellable, 2, cancellable),
+    ]);
+    solutions
+}
+
+/// all input generators of the compiled days, ordered by day
+///
+/// Day 10 has no generator, its lines are checked one by one by `check_line` instead.
+#[allow(clippy::vec_init_then_push)] // with only a single day compiled
+pub fn generators() -> Vec<Generator> {
+    #[allow(unused_mut)]
+    let mut generators = Vec::new();
+    #[cfg(feature = "day1")]
+    generators.push(generator!(day1, 1));
+    #[cfg(feature = "day2")]
+    generators.push(generator!(day2, 2));
+    #[cfg(feature = "day3")]
+    generators.push(generator!(day3, 3));
+    #[cfg(feature = "day4")]
+    generators.push(generator!(day4, 4));
+    #[cfg(feature = "day5")]
+    generators.push(generator!(day5, 5));
+    #[cfg(feature = "day6")]
+    generators.push(generator!(day6, 6));
+    #[cfg(feature = "day7")]
+    generators.push(generator!(day7, 7));
+    #[cfg(feature = "day8")]
+    generators.push(generator!(day8, 8));
+    #[cfg(feature = "day9")]
+    generators.push(generator!(day9, 9));
+    #[cfg(feature = "day10")]
+    generators.push(Generator {
+        day: 10,
+        parse: |input| {
+            for line in input.lines() {
+                let _ = crate::day10::check_line(line);
+            }
+            Ok(())
+        },
+    });
+    #[cfg(feature = "day11")]
+    generators.push(generator!(day11, 11));
+    #[cfg(feature = "day12")]
+    generators.push(generator!(day12, 12));
+    #[cfg(feature = "day13")]
+    generators.push(generator!(day13, 13));
+    #[cfg(feature = "day14")]
+    generators.push(generator!(day14, 14));
+    #[cfg(feature = "day15")]
+    generators.push(generator!(day15, 15));
+    generators
+}
+
+/// parses the input of given day and, if its generator accepts it, runs every part on it
+///
+/// Long running parts are stopped after `budget`. Errors are expected for arbitrary input, so
+/// only panics of generators or solvers surface, which is what the fuzz targets look for.
+pub fn exercise(day: u8, input: &str, budget: Duration) -> anyhow::Result<()> {
+    generator(day)
+        .ok_or_else(|| anyhow!("day {} is not compiled", day))?
+        .parse(input)?;
+    for solution in solutions().into_iter().filter(|s| s.day == day) {
+        solution.run_cancellable(input, &Cancel::with_budget(budget))?;
+    }
+    Ok(())
+}
+
+/// generator of given day, if that day is compiled
+pub fn generator(day: u8) -> Option<Generator> {
+    generators().into_iter().find(|g| g.day == day)
+}
+
+/// explanations of the compiled days, only a few parts have one
+#[allow(clippy::vec_init_then_push)] // with only a single day compiled
+pub fn explainers() -> Vec<Explainer> {
+    #[allow(unused_mut)]
+    let mut explainers = Vec::new();
+    #[cfg(feature = "day1")]
+    explainers.extend([
+        explainer!(day1, 1, explain1, 1),
+        explainer!(day1, 1, explain2, 2),
+    ]);
+    #[cfg(feature = "day2")]
+    explainers.extend([
+        explainer!(day2, 2, explain1, 1, fallible),
+        explainer!(day2, 2, explain2, 2, fallible),
+    ]);
+    #[cfg(feature = "day3")]
+    explainers.extend([
+        explainer!(day3, 3, explain1, 1),
+        explainer!(day3, 3, explain2, 2),
+    ]);
+    #[cfg(feature = "day4")]
+    explainers.extend([
+        explainer!(day4, 4, explain1, 1),
+        explainer!(day4, 4, explain2, 2),
+    ]);
+    #[cfg(feature = "day9")]
+    explainers.push(explainer!(day9, 9, explain2, 2));
+    #[cfg(feature = "day15")]
+    explainers.push(explainer!(day15, 15, explain1, 1));
+    explainers
+}
+
+/// explanation of given day part, if it has one and the day is compiled
+pub fn explainer(day: u8, part: u8) -> Option<Explainer> {
+    explainers()
+        .into_iter()
+        .find(|e| e.day == day && e.part == part)
+}
+
+/// animations of the compiled grid days which simulate step by step
+#[allow(clippy::vec_init_then_push)] // with only a single day compiled
+pub fn animations() -> Vec<Animation> {
+    #[allow(unused_mut)]
+    let mut animations = Vec::new();
+    #[cfg(feature = "day11")]
+    animations.push(Animation {
+        day: 11,
+        frames: |input| Ok(crate::day11::frames(&crate::day11::parse_input(input)?)),
+    });
+    #[cfg(feature = "day13")]
+    animations.push(Animation {
+        day: 13,
+        frames: |input| Ok(crate::day13::frames(&crate::day13::parse_input(input)?)),
+    });
+    animations
+}
+
+/// input validators of all compiled days, ordered by day
+///
+/// Days without dedicated invariants report a failing generator as their only violation.
+#[allow(clippy::vec_init_then_push)] // with only a single day compiled
+pub fn validators() -> Vec<Validator> {
+    #[allow(unused_mut)]
+    let mut validators = Vec::new();
+    #[cfg(feature = "day1")]
+    validators.push(validator!(day1, 1));
+    #[cfg(feature = "day2")]
+    validators.push(validator!(day2, 2));
+    #[cfg(feature = "day3")]
+    validators.push(validator!(day3, 3));
+    #[cfg(feature = "day4")]
+    validators.push(validator!(day4, 4, crate::day4::validate));
+    #[cfg(feature = "day5")]
+    validators.push(validator!(day5, 5));
+    #[cfg(feature = "day6")]
+    validators.push(validator!(day6, 6));
+    #[cfg(feature = "day7")]
+    validators.push(validator!(day7, 7));
+    #[cfg(feature = "day8")]
+    validators.push(validator!(day8, 8, crate::day8::validate));
+    #[cfg(feature = "day9")]
+    validators.push(validator!(day9, 9, crate::validate::digit_grid));
+    #[cfg(feature = "day10")]
+    validators.push(Validator {
+        day: 10,
+        check: |input| {
+            let mut violations = Vec::new();
+            for (idx, line) in input.lines().enumerate() {
+                if let Some((column, c)) = line
+                    .chars()
+                    .enumerate()
+                    .find(|(_, c)| !"()[]{}<>".contains(*c))
+                {
+                    violations.push(Violation::at(
+                        idx + 1,
+                        column + 1,
+                        format!("invalid character `{}`", c),
+                    ));
+                }
+            }
+            violations
+        },
+    });
+    #[cfg(feature = "day11")]
+    validators.push(validator!(day11, 11, crate::validate::digit_grid));
+    #[cfg(feature = "day12")]
+    validators.push(validator!(day12, 12));
+    #[cfg(feature = "day13")]
+    validators.push(validator!(day13, 13, crate::day13::validate));
+    #[cfg(feature = "day14")]
+    validators.push(validator!(day14, 14));
+    #[cfg(feature = "day15")]
+    validators.push(validator!(day15, 15, crate::validate::digit_grid));
+    validators
+}
+
+/// suspicious readings of a day 1 sonar sweep, in which empty lines are missing readings
+#[cfg(feature = "day1")]
+pub fn anomalies(input: &str) -> anyhow::Result<Vec<Anomaly>> {
+    crate::day1::anomalies(input)
+}
+
+/// path of the puzzle input as used by cargo-aoc
+pub fn input_path(day: u8) -> PathBuf {
+    PathBuf::from(format!("input/{}/day{}.txt", YEAR, day))
+}
+
+/// reads the puzzle input of given day, trimming trailing newlines like cargo-aoc does
+pub fn load_input(day: u8) -> std::io::Result<String> {
+    let input = fs::read_to_string(input_path(day))?;
+    Ok(input.trim_end_matches('\n').to_string())
+}
+
+/// path of the first example of the puzzle description, as extracted into the fuzz seeds
+pub fn example_path(day: u8) -> std::io::Result<PathBuf> {
+    let mut examples: Vec<PathBuf> = fs::read_dir(format!("fuzz/seeds/day{}", day))?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<_, _>>()?;
+    examples.sort();
+    examples
+        .into_iter()
+        .next()
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "no example"))
+}
+
+/// reads the first example of given day, trimming trailing newlines like `load_input`
+pub fn load_example(day: u8) -> std::io::Result<String> {
+    let input = fs::read_to_string(example_path(day)?)?;
+    Ok(input.trim_end_matches('\n').to_string())
+}
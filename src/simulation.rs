@@ -0,0 +1,193 @@
+//! Step-by-step simulations like cellular automata
+//!
+//! A `Simulation` owns a state and a step function computing the next state. As an iterator it
+//! yields every following state, so all iterator adapters can be used on it as well.
+
+use crate::cancel::{Cancel, Cancelled};
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// A state evolving by repeatedly applying a step function
+pub struct Simulation<S, F>
+where
+    F: FnMut(&S) -> S,
+{
+    state: S,
+    step: F,
+    steps: usize,
+}
+
+impl<S, F> Simulation<S, F>
+where
+    F: FnMut(&S) -> S,
+{
+    pub fn new(state: S, step: F) -> Simulation<S, F> {
+        Simulation {
+            state,
+            step,
+            steps: 0,
+        }
+    }
+
+    /// current state
+    pub fn state(&self) -> &S {
+        &self.state
+    }
+
+    pub fn into_state(self) -> S {
+        self.state
+    }
+
+    /// number of steps simulated so far
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    /// simulates a single step
+    pub fn step(&mut self) -> &S {
+        self.state = (self.step)(&self.state);
+        self.steps += 1;
+        &self.state
+    }
+
+    /// simulates given number of steps
+    pub fn run(&mut self, steps: usize) -> &S {
+        for _ in 0..steps {
+            self.step();
+        }
+        &self.state
+    }
+
+    /// simulates until the predicate holds for the current state, giving up after `limit` steps
+    ///
+    /// Returns the total number of steps simulated when the predicate holds first, which is the
+    /// current step count if it already holds for the current state.
+    pub fn run_until<P>(&mut self, predicate: P, limit: usize) -> Option<usize>
+    where
+        P: Fn(&S) -> bool,
+    {
+        self.run_until_cancellable(predicate, limit, &Cancel::new())
+            .expect("never cancelled")
+    }
+
+    /// like `run_until`, checking the cancellation token before every step
+    pub fn run_until_cancellable<P>(
+        &mut self,
+        predicate: P,
+        limit: usize,
+        cancel: &Cancel,
+    ) -> Result<Option<usize>, Cancelled>
+    where
+        P: Fn(&S) -> bool,
+    {
+        for _ in 0..limit {
+            if predicate(&self.state) {
+                return Ok(Some(self.steps));
+            }
+            cancel.check(|| format!("{} steps simulated", self.steps))?;
+            self.step();
+        }
+        Ok(predicate(&self.state).then_some(self.steps))
+    }
+}
+
+impl<S, F> Simulation<S, F>
+where
+    S: Clone + Eq + Hash,
+    F: FnMut(&S) -> S,
+{
+    /// simulates given number of steps, skipping ahead as soon as a state repeats
+    ///
+    /// Every visited state is kept until a cycle is found, so this only pays off for
+    /// simulations which run into a cycle after a moderate number of steps.
+    pub fn run_cyclic(&mut self, steps: usize) -> &S {
+        let mut seen: HashMap<S, usize> = HashMap::new();
+        let mut history: Vec<S> = Vec::new();
+        for done in 0..steps {
+            if let Some(&cycle_start) = seen.get(&self.state) {
+                let cycle_len = done - cycle_start;
+                let remaining = (steps - done) % cycle_len;
+                self.state = history[cycle_start + remaining].clone();
+                self.steps += steps - done;
+                return &self.state;
+            }
+            seen.insert(self.state.clone(), done);
+            history.push(self.state.clone());
+            self.step();
+        }
+        &self.state
+    }
+}
+
+/// yields the state after every step, endlessly
+impl<S, F> Iterator for Simulation<S, F>
+where
+    S: Clone,
+    F: FnMut(&S) -> S,
+{
+    type Item = S;
+
+    fn next(&mut self) -> Option<S> {
+        Some(self.step().clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// the rule 90 cellular automaton on a ring of 6 cells
+    fn rule90(cells: &[bool; 6]) -> [bool; 6] {
+        let mut next = [false; 6];
+        for (i, cell) in next.iter_mut().enumerate() {
+            *cell = cells[(i + 5) % 6] ^ cells[(i + 1) % 6];
+        }
+        next
+    }
+
+    #[test]
+    fn run_and_iterate() {
+        let mut counter = Simulation::new(0u64, |n| n + 3);
+        assert_eq!(*counter.run(4), 12);
+        assert_eq!(counter.steps(), 4);
+        assert_eq!(counter.by_ref().take(2).collect::<Vec<_>>(), [15, 18]);
+        assert_eq!(counter.steps(), 6);
+        assert_eq!(counter.into_state(), 18);
+    }
+
+    #[test]
+    fn run_until_with_limit() {
+        let mut collatz = Simulation::new(27u64, |&n| if n % 2 == 0 { n / 2 } else { 3 * n + 1 });
+        assert_eq!(collatz.run_until(|&n| n == 1, 100), None);
+        assert_eq!(collatz.steps(), 100);
+        assert_eq!(collatz.run_until(|&n| n == 1, 100), Some(111));
+        // holds already, no step needed
+        assert_eq!(collatz.run_until(|&n| n == 1, 0), Some(111));
+
+        let cancel = Cancel::new();
+        let mut counter = Simulation::new(0u64, |n| n + 1);
+        assert_eq!(
+            counter.run_until_cancellable(|&n| n == 10, 100, &cancel),
+            Ok(Some(10))
+        );
+        cancel.cancel();
+        let cancelled = counter
+            .run_until_cancellable(|&n| n == 20, 100, &cancel)
+            .unwrap_err();
+        assert_eq!(cancelled.progress, "10 steps simulated");
+    }
+
+    #[test]
+    fn cycles_are_skipped() {
+        let start = [false, false, true, true, false, false];
+        let mut naive = Simulation::new(start, rule90);
+        let mut cyclic = Simulation::new(start, rule90);
+        for steps in [0, 1, 5, 17, 100] {
+            assert_eq!(naive.run(steps), cyclic.run_cyclic(steps));
+            assert_eq!(naive.steps(), cyclic.steps());
+        }
+        let mut clock = Simulation::new(0u64, |n| (n + 7) % 100);
+        assert_eq!(*clock.run_cyclic(1_000_000_007), 7 * 1_000_000_007 % 100);
+        assert_eq!(clock.steps(), 1_000_000_007);
+    }
+}
@@ -0,0 +1,70 @@
+//! Rendering of example states for snapshot tests
+//!
+//! Tests store the states of their examples as [insta](https://insta.rs) snapshots in
+//! `src/snapshots`, instead of spelling out every grid. After changing a rendering or a
+//! simulation, review and accept the differing snapshots with
+//!
+//! ```bash
+//! cargo insta review
+//! ```
+//!
+//! or overwrite all of them at once with `INSTA_UPDATE=always cargo test`.
+
+#[cfg(any(feature = "day9", feature = "day15"))]
+use ndarray::Array2;
+use std::fmt::Display;
+
+/// labeled states one after another, separated by empty lines
+pub(crate) fn states<L, S>(states: impl IntoIterator<Item = (L, S)>) -> String
+where
+    L: Display,
+    S: AsRef<str>,
+{
+    states
+        .into_iter()
+        .map(|(label, state)| format!("{}:\n{}\n", label, state.as_ref().trim_end()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// renders a grid with one character per cell
+#[cfg(any(feature = "day9", feature = "day15"))]
+pub(crate) fn grid<T, C>(grid: &Array2<T>, cell: C) -> String
+where
+    C: Fn((usize, usize), &T) -> char,
+{
+    grid.rows()
+        .into_iter()
+        .enumerate()
+        .map(|(y, row)| {
+            row.iter()
+                .enumerate()
+                .map(|(x, value)| cell((y, x), value))
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn labeled_states() {
+        assert_eq!(
+            states([("start", "ab\ncd\n"), ("end", "ef")]),
+            "start:\nab\ncd\n\nend:\nef\n"
+        );
+    }
+
+    #[test]
+    #[cfg(any(feature = "day9", feature = "day15"))]
+    fn grid_cells() {
+        let digits = Array2::from_shape_vec((2, 3), vec![1u8, 2, 3, 4, 5, 6]).unwrap();
+        let odd = grid(&digits, |_, d| if d % 2 == 1 { '#' } else { '.' });
+        assert_eq!(odd, "#.#\n.#.");
+        let diagonal = grid(&digits, |(y, x), _| if y == x { '\\' } else { ' ' });
+        assert_eq!(diagonal, "\\  \n \\ ");
+    }
+}
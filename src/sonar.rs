@@ -0,0 +1,434 @@
+//! Analysis of sonar sweeps: how the sums of sliding windows of depth measurements change
+//!
+//! The sums of two neighbouring windows of size `w` share all but one measurement each, so the
+//! sum of the window starting at `i + 1` differs from the one starting at `i` by exactly
+//! `a[i + w] - a[i]`. Changes are computed from that difference while streaming over the
+//! measurements, keeping only the last `w` of them and never building the sums themselves.
+//!
+//! Sweeps with missing readings can also be searched for suspicious depths with
+//! [`detect_anomalies`].
+
+use std::collections::VecDeque;
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+
+/// How the sum of a window differs from the sum of the window before it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowChange {
+    /// index of the later window, which is the index of its first measurement
+    pub index: usize,
+    /// sum of the later window minus the sum of the earlier one, wide enough for any two depths
+    pub delta: i128,
+}
+
+impl WindowChange {
+    pub fn is_increase(&self) -> bool {
+        self.delta > 0
+    }
+}
+
+/// Iterator over the changes between the sums of consecutive windows, see [`window_changes`]
+#[derive(Debug, Clone)]
+pub struct WindowChanges<I> {
+    depths: I,
+    window: usize,
+    previous: VecDeque<i64>,
+    index: usize,
+}
+
+impl<I> Iterator for WindowChanges<I>
+where
+    I: Iterator<Item = i64>,
+{
+    type Item = WindowChange;
+
+    fn next(&mut self) -> Option<WindowChange> {
+        while self.previous.len() < self.window {
+            self.previous.push_back(self.depths.next()?);
+        }
+        let depth = self.depths.next()?;
+        let left = self.previous.pop_front().expect("window is never empty");
+        self.previous.push_back(depth);
+        self.index += 1;
+        Some(WindowChange {
+            index: self.index,
+            delta: i128::from(depth) - i128::from(left),
+        })
+    }
+}
+
+/// changes between the sums of all consecutive windows of `window` measurements
+///
+/// Panics if the window size is 0.
+pub fn window_changes<I>(depths: I, window: usize) -> WindowChanges<I::IntoIter>
+where
+    I: IntoIterator<Item = i64>,
+{
+    assert!(window > 0, "window size must be at least 1");
+    WindowChanges {
+        depths: depths.into_iter(),
+        window,
+        previous: VecDeque::with_capacity(window),
+        index: 0,
+    }
+}
+
+/// number of windows whose sum is larger than the sum of the window before
+pub fn count_increases<I>(depths: I, window: usize) -> usize
+where
+    I: IntoIterator<Item = i64>,
+{
+    window_changes(depths, window)
+        .filter(WindowChange::is_increase)
+        .count()
+}
+
+/// Summary of how the window sums of a sweep change
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SonarReport {
+    /// number of windows in the sweep
+    pub windows: usize,
+    /// indices of the windows whose sum is larger than the sum of the window before
+    pub increases: Vec<usize>,
+    /// indices of the longest sequence of windows with strictly increasing sums, the earliest
+    /// one on ties, `None` if no sum increases
+    pub longest_run: Option<Range<usize>>,
+    /// change with the largest magnitude in either direction, the earliest one on ties
+    pub largest_jump: Option<WindowChange>,
+}
+
+/// analyses the window sums of a sweep in a single pass
+pub fn analyze<I>(depths: I, window: usize) -> SonarReport
+where
+    I: IntoIterator<Item = i64>,
+{
+    let mut report = SonarReport::default();
+    let mut run_start = None;
+    let mut measurements = 0usize;
+    let depths = depths.into_iter().inspect(|_| measurements += 1);
+    for change in window_changes(depths, window) {
+        if change.is_increase() {
+            report.increases.push(change.index);
+            let start = *run_start.get_or_insert(change.index - 1);
+            let run = start..change.index + 1;
+            if report
+                .longest_run
+                .as_ref()
+                .is_none_or(|r| run.len() > r.len())
+            {
+                report.longest_run = Some(run);
+            }
+        } else {
+            run_start = None;
+        }
+        if report
+            .largest_jump
+            .is_none_or(|jump| change.delta.abs() > jump.delta.abs())
+        {
+            report.largest_jump = Some(change);
+        }
+    }
+    report.windows = (measurements + 1).saturating_sub(window);
+    report
+}
+
+/// a sweep with one depth per line, empty lines are missing readings
+pub fn parse_sweep(input: &str) -> anyhow::Result<Vec<Option<i64>>> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(idx, line)| match line.trim() {
+            "" => Ok(None),
+            depth => depth
+                .parse()
+                .map(Some)
+                .map_err(|_| anyhow!("invalid depth `{}` on line {}", depth, idx + 1)),
+        })
+        .collect()
+}
+
+/// Limits beyond which readings are flagged by [`detect_anomalies`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnomalyConfig {
+    /// number of readings on either side of a reading whose median it is compared with
+    pub median_radius: usize,
+    /// largest distance from the rolling median which is not an outlier
+    pub max_deviation: i64,
+    /// largest decrease of the depth from one reading to the next which is not a sudden drop
+    pub max_drop: i64,
+}
+
+impl Default for AnomalyConfig {
+    /// limits well above the noise of real sweeps, which stays within ±45 between readings
+    fn default() -> AnomalyConfig {
+        AnomalyConfig {
+            median_radius: 3,
+            max_deviation: 50,
+            max_drop: 50,
+        }
+    }
+}
+
+/// Why a reading is suspicious
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reason {
+    /// the depth is too far from the median of the readings around it
+    Outlier { median: i64 },
+    /// the depth decreased too much since the previous reading
+    Drop { previous: i64 },
+    /// this and the following readings are missing
+    Gap { missing: usize },
+}
+
+impl Display for Reason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Reason::Outlier { median } => write!(f, "outlier from rolling median {}", median),
+            Reason::Drop { previous } => write!(f, "sudden drop from {}", previous),
+            Reason::Gap { missing: 1 } => write!(f, "gap of 1 missing reading"),
+            Reason::Gap { missing } => write!(f, "gap of {} missing readings", missing),
+        }
+    }
+}
+
+/// A flagged reading
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Anomaly {
+    pub index: usize,
+    /// the flagged depth, `None` for gaps
+    pub depth: Option<i64>,
+    pub reason: Reason,
+}
+
+/// all suspicious readings of a sweep, ordered by index
+///
+/// A reading can be flagged for more than one reason. Every run of missing readings is a single
+/// gap at its first index, readings next to a gap are never sudden drops and medians are taken
+/// over the readings present only. Differences are taken in 128 bits, so any two depths compare.
+pub fn detect_anomalies(sweep: &[Option<i64>], config: &AnomalyConfig) -> Vec<Anomaly> {
+    let mut anomalies = Vec::new();
+    let mut idx = 0;
+    while idx < sweep.len() {
+        let Some(depth) = sweep[idx] else {
+            let missing = sweep[idx..].iter().take_while(|d| d.is_none()).count();
+            anomalies.push(Anomaly {
+                index: idx,
+                depth: None,
+                reason: Reason::Gap { missing },
+            });
+            idx += missing;
+            continue;
+        };
+        let mut flag = |reason| {
+            anomalies.push(Anomaly {
+                index: idx,
+                depth: Some(depth),
+                reason,
+            })
+        };
+        let median = rolling_median(sweep, idx, config.median_radius);
+        if (i128::from(depth) - i128::from(median)).abs() > i128::from(config.max_deviation) {
+            flag(Reason::Outlier { median });
+        }
+        if let Some(Some(previous)) = idx.checked_sub(1).map(|prev| sweep[prev]) {
+            if i128::from(previous) - i128::from(depth) > i128::from(config.max_drop) {
+                flag(Reason::Drop { previous });
+            }
+        }
+        idx += 1;
+    }
+    anomalies
+}
+
+/// median of the readings present within `radius` of given index, the lower one of the middle
+/// two for an even number of readings
+fn rolling_median(sweep: &[Option<i64>], idx: usize, radius: usize) -> i64 {
+    let end = (idx + radius + 1).min(sweep.len());
+    let mut around: Vec<i64> = sweep[idx.saturating_sub(radius)..end]
+        .iter()
+        .flatten()
+        .copied()
+        .collect();
+    around.sort_unstable();
+    around[(around.len() - 1) / 2]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: [i64; 10] = [199, 200, 208, 210, 200, 207, 240, 269, 260, 263];
+
+    /// sums every window and compares them, as the puzzle description does
+    fn naive_increases(depths: &[i64], window: usize) -> Vec<usize> {
+        let sums: Vec<i64> = depths.windows(window).map(|w| w.iter().sum()).collect();
+        (1..sums.len()).filter(|&i| sums[i] > sums[i - 1]).collect()
+    }
+
+    #[test]
+    fn example_windows() {
+        assert_eq!(count_increases(EXAMPLE, 1), 7);
+        assert_eq!(count_increases(EXAMPLE, 3), 5);
+
+        let single = analyze(EXAMPLE, 1);
+        assert_eq!(single.windows, 10);
+        assert_eq!(single.increases, [1, 2, 3, 5, 6, 7, 9]);
+        // 199 200 208 210 and 200 207 240 269 are both 4 long
+        assert_eq!(single.longest_run, Some(0..4));
+        assert_eq!(
+            single.largest_jump,
+            Some(WindowChange {
+                index: 6,
+                delta: 33
+            })
+        );
+
+        // sums 607 618 618 617 647 716 769 792
+        let triple = analyze(EXAMPLE, 3);
+        assert_eq!(triple.windows, 8);
+        assert_eq!(triple.increases, [1, 4, 5, 6, 7]);
+        assert_eq!(triple.longest_run, Some(3..8));
+        assert_eq!(
+            triple.largest_jump,
+            Some(WindowChange {
+                index: 5,
+                delta: 69
+            })
+        );
+    }
+
+    #[test]
+    fn short_sweeps() {
+        assert_eq!(analyze([], 1), SonarReport::default());
+        let whole = analyze(EXAMPLE, 10);
+        assert_eq!(whole.windows, 1);
+        assert_eq!(whole.largest_jump, None);
+        assert_eq!(analyze(EXAMPLE, 11).windows, 0);
+        let report = analyze([3, 2, 1], 1);
+        assert_eq!(report.windows, 3);
+        assert_eq!(report.longest_run, None);
+        assert_eq!(report.largest_jump.map(|jump| jump.delta), Some(-1));
+    }
+
+    #[test]
+    fn extreme_depths() {
+        let report = analyze([i64::MAX, i64::MIN, i64::MAX], 1);
+        assert_eq!(report.increases, [2]);
+        assert_eq!(
+            report.largest_jump.map(|jump| jump.delta),
+            Some(-(1 << 64) + 1)
+        );
+    }
+
+    #[test]
+    fn matches_summed_windows() {
+        // pseudo random depths, with plenty of equal sums
+        let mut seed = 2021u64;
+        let depths: Vec<i64> = (0..500)
+            .map(|_| {
+                seed = seed
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                (seed >> 60) as i64
+            })
+            .collect();
+        for window in 1..=8 {
+            let streamed = analyze(depths.iter().copied(), window);
+            assert_eq!(streamed.increases, naive_increases(&depths, window));
+            assert_eq!(streamed.windows, depths.len() - window + 1);
+        }
+    }
+
+    /// a slowly deepening sea floor with a little noise
+    fn synthetic_sweep(len: usize) -> Vec<Option<i64>> {
+        (0..len as i64)
+            .map(|i| Some(1000 + 3 * i + i % 5))
+            .collect()
+    }
+
+    #[test]
+    fn sweeps_parse() {
+        assert_eq!(
+            parse_sweep("199\n\n\n210\n").unwrap(),
+            [Some(199), None, None, Some(210)]
+        );
+        assert_eq!(
+            parse_sweep("199\n2o0").unwrap_err().to_string(),
+            "invalid depth `2o0` on line 2"
+        );
+    }
+
+    #[test]
+    fn clean_sweeps() {
+        let config = AnomalyConfig::default();
+        assert_eq!(detect_anomalies(&synthetic_sweep(200), &config), []);
+        let example: Vec<Option<i64>> = EXAMPLE.into_iter().map(Some).collect();
+        assert_eq!(detect_anomalies(&example, &config), []);
+        assert_eq!(detect_anomalies(&[], &config), []);
+    }
+
+    #[test]
+    fn flagged_readings() {
+        let mut sweep = synthetic_sweep(60);
+        // a single spike: an outlier, followed by a drop back to the sea floor
+        sweep[10] = Some(1500);
+        // the sea floor rises by 200 and stays there, only the step itself is a drop
+        for depth in sweep[30..].iter_mut() {
+            *depth = depth.map(|d| d - 200);
+        }
+        // the sonar stops answering for a while
+        sweep[45..48].fill(None);
+        sweep[59] = None;
+
+        let anomalies = detect_anomalies(&sweep, &AnomalyConfig::default());
+        let flagged: Vec<(usize, String)> = anomalies
+            .iter()
+            .map(|a| (a.index, a.reason.to_string()))
+            .collect();
+        assert_eq!(
+            flagged,
+            [
+                (10, "outlier from rolling median 1034".to_string()),
+                (11, "sudden drop from 1500".to_string()),
+                (30, "sudden drop from 1091".to_string()),
+                (45, "gap of 3 missing readings".to_string()),
+                (59, "gap of 1 missing reading".to_string()),
+            ]
+        );
+        assert_eq!(anomalies[0].depth, Some(1500));
+        assert_eq!(anomalies[3].depth, None);
+
+        // without any tolerance, the noise of the clean sweep drops every 5 readings
+        let strict = AnomalyConfig {
+            max_drop: 0,
+            ..AnomalyConfig::default()
+        };
+        let drops: Vec<usize> = detect_anomalies(&synthetic_sweep(30), &strict)
+            .iter()
+            .map(|a| a.index)
+            .collect();
+        assert_eq!(drops, [5, 10, 15, 20, 25]);
+    }
+
+    #[test]
+    fn extreme_readings() {
+        let sweep = [Some(i64::MAX), Some(i64::MIN), Some(i64::MAX)];
+        let reasons: Vec<String> = detect_anomalies(&sweep, &AnomalyConfig::default())
+            .iter()
+            .map(|a| format!("{} {}", a.index, a.reason))
+            .collect();
+        assert_eq!(
+            reasons,
+            [
+                format!("1 outlier from rolling median {}", i64::MAX),
+                format!("1 sudden drop from {}", i64::MAX),
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "window size must be at least 1")]
+    fn empty_window() {
+        count_increases(EXAMPLE, 0);
+    }
+}
@@ -0,0 +1,654 @@
+//! Following the planned course of the submarine
+//!
+//! A small interpreter follows commands, while a [`Semantics`] defines how each of them changes
+//! the state of the submarine. Both interpretations of day 2 are built in, others can be
+//! plugged in by implementing the trait. The state after every command makes up a trajectory,
+//! which can be summarised and exported as CSV for spreadsheets or as SVG for a quick look at
+//! the dive.
+//!
+//! The other way round, [`plan`] finds courses reaching a target under the aim interpretation.
+
+use std::fmt::{Display, Formatter, Write};
+
+/// A planned command
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Instr {
+    Forward(i64),
+    Down(i64),
+    Up(i64),
+    /// moves backwards, undoing a `forward` of the same amount
+    Back(i64),
+    /// rises straight to the surface
+    Surface,
+    /// follows the commands of the block given number of times
+    Repeat(usize, Vec<Instr>),
+}
+
+/// writes the command as in a planned course, blocks over several lines with indented commands
+impl Display for Instr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Instr::Forward(amount) => write!(f, "forward {}", amount),
+            Instr::Down(amount) => write!(f, "down {}", amount),
+            Instr::Up(amount) => write!(f, "up {}", amount),
+            Instr::Back(amount) => write!(f, "back {}", amount),
+            Instr::Surface => write!(f, "surface"),
+            Instr::Repeat(times, block) => {
+                writeln!(f, "repeat {} {{", times)?;
+                for line in block.iter().flat_map(|instr| {
+                    instr
+                        .to_string()
+                        .lines()
+                        .map(String::from)
+                        .collect::<Vec<_>>()
+                }) {
+                    writeln!(f, "  {}", line)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+/// Position and aim of the submarine, the aim stays 0 under the direct interpretation
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct State {
+    pub x: i64,
+    pub depth: i64,
+    pub aim: i64,
+}
+
+/// How single commands change the state of the submarine
+///
+/// Only `forward` and `down` need to be defined, the other commands default to their opposite
+/// or, for `surface`, to resetting depth and aim. Commands fail once the state would leave the
+/// range of `i64`.
+pub trait Semantics {
+    fn forward(&self, state: State, amount: i64) -> anyhow::Result<State>;
+
+    fn down(&self, state: State, amount: i64) -> anyhow::Result<State>;
+
+    fn up(&self, state: State, amount: i64) -> anyhow::Result<State> {
+        self.down(state, in_range(amount.checked_neg())?)
+    }
+
+    fn back(&self, state: State, amount: i64) -> anyhow::Result<State> {
+        self.forward(state, in_range(amount.checked_neg())?)
+    }
+
+    fn surface(&self, state: State) -> anyhow::Result<State> {
+        Ok(State {
+            depth: 0,
+            aim: 0,
+            ..state
+        })
+    }
+}
+
+/// result of checked arithmetic on a state, which is `None` on overflow
+pub fn in_range(value: Option<i64>) -> anyhow::Result<i64> {
+    value.ok_or_else(|| anyhow!("submarine leaves the range of 64 bit numbers"))
+}
+
+/// The interpretations of the puzzle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interpretation {
+    /// `down` and `up` change the depth, as first assumed in part 1
+    Direct,
+    /// `down` and `up` change the aim, `forward` also dives by aim times its amount, as in part 2
+    Aim,
+}
+
+impl Semantics for Interpretation {
+    fn forward(&self, state: State, amount: i64) -> anyhow::Result<State> {
+        let x = in_range(state.x.checked_add(amount))?;
+        Ok(match self {
+            Interpretation::Direct => State { x, ..state },
+            Interpretation::Aim => State {
+                x,
+                depth: in_range(
+                    state
+                        .aim
+                        .checked_mul(amount)
+                        .and_then(|dive| state.depth.checked_add(dive)),
+                )?,
+                ..state
+            },
+        })
+    }
+
+    fn down(&self, state: State, amount: i64) -> anyhow::Result<State> {
+        Ok(match self {
+            Interpretation::Direct => State {
+                depth: in_range(state.depth.checked_add(amount))?,
+                ..state
+            },
+            Interpretation::Aim => State {
+                aim: in_range(state.aim.checked_add(amount))?,
+                ..state
+            },
+        })
+    }
+}
+
+impl State {
+    /// state after following a command, including all repetitions of a block
+    pub fn apply<S: Semantics + ?Sized>(
+        self,
+        instr: &Instr,
+        semantics: &S,
+    ) -> anyhow::Result<State> {
+        follow(self, std::slice::from_ref(instr), semantics, &mut |_| {})
+    }
+}
+
+/// follows the commands from given state, passing the state after every single command to
+/// `visit`, blocks themselves are not visited
+fn follow<S, V>(
+    mut state: State,
+    instructions: &[Instr],
+    semantics: &S,
+    visit: &mut V,
+) -> anyhow::Result<State>
+where
+    S: Semantics + ?Sized,
+    V: FnMut(State),
+{
+    for instr in instructions {
+        state = match instr {
+            Instr::Forward(amount) => semantics.forward(state, *amount)?,
+            Instr::Down(amount) => semantics.down(state, *amount)?,
+            Instr::Up(amount) => semantics.up(state, *amount)?,
+            Instr::Back(amount) => semantics.back(state, *amount)?,
+            Instr::Surface => semantics.surface(state)?,
+            Instr::Repeat(times, block) => {
+                for _ in 0..*times {
+                    state = follow(state, block, semantics, visit)?;
+                }
+                continue;
+            }
+        };
+        visit(state);
+    }
+    Ok(state)
+}
+
+/// final state after following all commands from the surface
+pub fn execute<S: Semantics + ?Sized>(
+    instructions: &[Instr],
+    semantics: &S,
+) -> anyhow::Result<State> {
+    follow(State::default(), instructions, semantics, &mut |_| {})
+}
+
+/// the starting state at the surface followed by the state after every command, repeated
+/// commands once for every repetition
+pub fn trajectory<S: Semantics + ?Sized>(
+    instructions: &[Instr],
+    semantics: &S,
+) -> anyhow::Result<Vec<State>> {
+    let mut states = vec![State::default()];
+    follow(State::default(), instructions, semantics, &mut |state| {
+        states.push(state)
+    })?;
+    Ok(states)
+}
+
+/// Key figures of a trajectory
+#[derive(Debug, Clone, PartialEq)]
+pub struct Summary {
+    pub end: State,
+    pub max_depth: i64,
+    /// length of the path through the water, changes of the aim alone do not move
+    pub distance: f64,
+}
+
+/// summary of a trajectory as returned by [`trajectory`], which starts with at least one state
+pub fn summarize(trajectory: &[State]) -> Summary {
+    let distance = trajectory
+        .windows(2)
+        .map(|pair| {
+            // as floats, the difference of two extreme states does not fit into `i64`
+            let dx = pair[1].x as f64 - pair[0].x as f64;
+            let dy = pair[1].depth as f64 - pair[0].depth as f64;
+            dx.hypot(dy)
+        })
+        .sum();
+    Summary {
+        end: trajectory[trajectory.len() - 1],
+        max_depth: trajectory.iter().map(|s| s.depth).max().unwrap_or_default(),
+        distance,
+    }
+}
+
+/// one line per state with its step, the starting state is step 0
+pub fn to_csv(trajectory: &[State]) -> String {
+    let mut csv = String::from("step,x,depth,aim\n");
+    for (step, state) in trajectory.iter().enumerate() {
+        writeln!(csv, "{},{},{},{}", step, state.x, state.depth, state.aim).unwrap();
+    }
+    csv
+}
+
+/// the path as polyline, stretched to fill the picture as depths easily exceed positions
+pub fn to_svg(trajectory: &[State]) -> String {
+    let min_x = trajectory.iter().map(|s| s.x).min().unwrap_or_default();
+    let max_x = trajectory.iter().map(|s| s.x).max().unwrap_or_default();
+    let min_depth = trajectory.iter().map(|s| s.depth).min().unwrap_or_default();
+    let max_depth = trajectory.iter().map(|s| s.depth).max().unwrap_or_default();
+    let points: Vec<String> = trajectory
+        .iter()
+        .map(|s| format!("{},{}", s.x, s.depth))
+        .collect();
+    format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="800" height="400" viewBox="{} {} {} {}" preserveAspectRatio="none">
+  <polyline points="{}" fill="none" stroke="navy" stroke-width="2" vector-effect="non-scaling-stroke"/>
+</svg>
+"#,
+        min_x,
+        min_depth,
+        max_x.saturating_sub(min_x).max(1),
+        max_depth.saturating_sub(min_depth).max(1),
+        points.join(" ")
+    )
+}
+
+/// What a planned course should use as little of as possible
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Objective {
+    /// number of commands
+    FewestCommands,
+    /// sum of the amounts of all commands
+    LeastMagnitude,
+}
+
+/// course of `forward`, `down` and `up` commands with positive amounts which reaches given
+/// horizontal position and depth from the surface under the aim interpretation
+///
+/// Every unit moved forward dives by the aim at that time, so the depth is the sum of the aims
+/// of all units. A course therefore needs at most three commands: one to aim at the whole
+/// depth, surrounded by forwards, and only one aim if the depth is a multiple of the position.
+/// Using as little magnitude as possible, the aims change by as little as possible: the course
+/// aims one less than the depth per unit rounded up, and then one more for the last units,
+/// `x + ceil(|depth| / x)` in total.
+pub fn plan(x: i64, depth: i64, objective: Objective) -> anyhow::Result<Vec<Instr>> {
+    if x < 0 {
+        bail!("horizontal position {} is behind the start", x);
+    }
+    if x == 0 {
+        if depth != 0 {
+            bail!("depth {} cannot be reached without moving forward", depth);
+        }
+        return Ok(Vec::new());
+    }
+    // aims of the same magnitude are reached by `down` below the surface and `up` above it
+    let aim = |amount: i64| match depth < 0 {
+        true => Instr::Up(amount),
+        false => Instr::Down(amount),
+    };
+    let units = depth
+        .checked_abs()
+        .ok_or_else(|| anyhow!("depth {} is out of range", depth))?;
+    let course = match objective {
+        _ if depth == 0 => vec![Instr::Forward(x)],
+        Objective::FewestCommands if units % x == 0 => vec![aim(units / x), Instr::Forward(x)],
+        Objective::FewestCommands => {
+            vec![Instr::Forward(x - 1), aim(units), Instr::Forward(1)]
+        }
+        Objective::LeastMagnitude if units % x == 0 => vec![aim(units / x), Instr::Forward(x)],
+        Objective::LeastMagnitude => {
+            let peak = units
+                .checked_add(x - 1)
+                .ok_or_else(|| anyhow!("course to {}, {} is out of range", x, depth))?
+                / x;
+            // units at the peak aim, all others are one less
+            let at_peak = units - (peak - 1) * x;
+            let mut course = Vec::new();
+            if peak > 1 {
+                course.push(aim(peak - 1));
+            }
+            if at_peak < x {
+                course.push(Instr::Forward(x - at_peak));
+            }
+            course.extend([aim(1), Instr::Forward(at_peak)]);
+            course
+        }
+    };
+    // checked against the interpreter, so a wrong course never goes unnoticed
+    let end = execute(&course, &Interpretation::Aim)?;
+    if (end.x, end.depth) != (x, depth) {
+        bail!(
+            "planned course ends at {}, {} instead of {}, {}",
+            end.x,
+            end.depth,
+            x,
+            depth
+        );
+    }
+    Ok(course)
+}
+
+/// sum of the amounts of all commands, blocks count as often as they are repeated
+pub fn magnitude(instructions: &[Instr]) -> anyhow::Result<i64> {
+    let mut sum = 0i64;
+    for instr in instructions {
+        let amount = match instr {
+            Instr::Forward(amount)
+            | Instr::Down(amount)
+            | Instr::Up(amount)
+            | Instr::Back(amount) => amount.checked_abs(),
+            Instr::Surface => Some(0),
+            Instr::Repeat(times, block) => i64::try_from(*times)
+                .ok()
+                .and_then(|times| times.checked_mul(magnitude(block).ok()?)),
+        };
+        sum = amount
+            .and_then(|amount| sum.checked_add(amount))
+            .ok_or_else(|| anyhow!("magnitude of the course is out of range"))?;
+    }
+    Ok(sum)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::{HashMap, HashSet};
+
+    const EXAMPLE: [Instr; 6] = [
+        Instr::Forward(5),
+        Instr::Down(5),
+        Instr::Forward(8),
+        Instr::Up(3),
+        Instr::Down(8),
+        Instr::Forward(2),
+    ];
+
+    #[test]
+    fn trajectories() {
+        let direct = trajectory(&EXAMPLE, &Interpretation::Direct).unwrap();
+        assert_eq!(direct.len(), 7);
+        assert_eq!(
+            direct[6],
+            execute(&EXAMPLE, &Interpretation::Direct).unwrap()
+        );
+        assert_eq!(
+            direct[4],
+            State {
+                x: 13,
+                depth: 2,
+                aim: 0
+            }
+        );
+
+        let aim = trajectory(&EXAMPLE, &Interpretation::Aim).unwrap();
+        assert_eq!(aim[6], execute(&EXAMPLE, &Interpretation::Aim).unwrap());
+        let depths: Vec<i64> = aim.iter().map(|s| s.depth).collect();
+        assert_eq!(depths, [0, 0, 0, 40, 40, 40, 60]);
+        assert_eq!(aim[5].aim, 10);
+    }
+
+    #[test]
+    fn summaries() {
+        let direct = summarize(&trajectory(&EXAMPLE, &Interpretation::Direct).unwrap());
+        assert_eq!(
+            direct.end,
+            State {
+                x: 15,
+                depth: 10,
+                aim: 0
+            }
+        );
+        assert_eq!(direct.max_depth, 10);
+        assert_eq!(direct.distance, 31.0);
+
+        let aim = summarize(&trajectory(&EXAMPLE, &Interpretation::Aim).unwrap());
+        assert_eq!((aim.end.x, aim.end.depth), (15, 60));
+        assert_eq!(aim.max_depth, 60);
+        let expected = 5.0 + 1664f64.sqrt() + 404f64.sqrt();
+        assert!((aim.distance - expected).abs() < 1e-9);
+
+        let surface = summarize(&trajectory(&[], &Interpretation::Aim).unwrap());
+        assert_eq!(surface.end, State::default());
+        assert_eq!(surface.distance, 0.0);
+    }
+
+    #[test]
+    fn exports() {
+        let aim = trajectory(&EXAMPLE, &Interpretation::Aim).unwrap();
+        assert_eq!(
+            to_csv(&aim),
+            "step,x,depth,aim
+0,0,0,0
+1,5,0,0
+2,5,0,5
+3,13,40,5
+4,13,40,2
+5,13,40,10
+6,15,60,10
+"
+        );
+        insta::assert_snapshot!(to_svg(&aim));
+    }
+
+    #[test]
+    fn extended_commands() {
+        let course = [
+            Instr::Down(4),
+            Instr::Repeat(3, vec![Instr::Forward(2), Instr::Up(1)]),
+            Instr::Back(1),
+            Instr::Surface,
+            Instr::Repeat(2, vec![Instr::Repeat(2, vec![Instr::Forward(1)])]),
+            Instr::Repeat(0, vec![Instr::Down(100)]),
+        ];
+        let direct = trajectory(&course, &Interpretation::Direct).unwrap();
+        let depths: Vec<i64> = direct.iter().map(|s| s.depth).collect();
+        assert_eq!(depths, [0, 4, 4, 3, 3, 2, 2, 1, 1, 0, 0, 0, 0, 0]);
+        assert_eq!(direct[13].x, 9);
+
+        // aims 4, 3 and 2 while moving forward, then 1 while moving back
+        let aim = trajectory(&course, &Interpretation::Aim).unwrap();
+        assert_eq!(aim[8].depth, 8 + 6 + 4 - 1);
+        assert_eq!(
+            aim[9],
+            State {
+                x: 5,
+                ..State::default()
+            }
+        );
+        assert_eq!(aim[13], execute(&course, &Interpretation::Aim).unwrap());
+        assert_eq!(
+            State::default()
+                .apply(&course[1], &Interpretation::Aim)
+                .unwrap(),
+            State {
+                x: 6,
+                depth: -6,
+                aim: -3
+            }
+        );
+    }
+
+    /// fewest commands and least magnitude to reach every state of a small area, found by a
+    /// breadth first search over commands of amounts up to the size of the area
+    fn searched_plans(size: i64) -> HashMap<(i64, i64), (usize, i64)> {
+        let mut best = HashMap::new();
+        let mut seen = HashSet::from([State::default()]);
+        let mut frontier = vec![State::default()];
+        for commands in 0..=3 {
+            for state in &frontier {
+                best.entry((state.x, state.depth))
+                    .or_insert((commands, i64::MAX));
+            }
+            let mut next = Vec::new();
+            for state in frontier {
+                for amount in 1..=size * size {
+                    for instr in [
+                        Instr::Forward(amount),
+                        Instr::Down(amount),
+                        Instr::Up(amount),
+                    ] {
+                        let moved = state.apply(&instr, &Interpretation::Aim).unwrap();
+                        let inside = moved.x <= size && moved.aim.abs() <= size * size;
+                        if inside && seen.insert(moved) {
+                            next.push(moved);
+                        }
+                    }
+                }
+            }
+            frontier = next;
+        }
+        // single units cost as much as the amounts they add up to, so the least magnitude is
+        // the number of unit commands
+        let mut seen = HashSet::from([State::default()]);
+        let mut frontier = vec![State::default()];
+        for magnitude in 0..=size + size * size {
+            for state in &frontier {
+                if let Some((_, least)) = best.get_mut(&(state.x, state.depth)) {
+                    *least = (*least).min(magnitude);
+                }
+            }
+            let mut next = Vec::new();
+            for state in frontier {
+                for instr in [Instr::Forward(1), Instr::Down(1), Instr::Up(1)] {
+                    let moved = state.apply(&instr, &Interpretation::Aim).unwrap();
+                    if moved.x <= size && moved.aim.abs() <= size * size && seen.insert(moved) {
+                        next.push(moved);
+                    }
+                }
+            }
+            frontier = next;
+        }
+        best
+    }
+
+    #[test]
+    fn planned_courses() {
+        let fewest = plan(15, 60, Objective::FewestCommands).unwrap();
+        assert_eq!(fewest, [Instr::Down(4), Instr::Forward(15)]);
+        let least = plan(15, 60, Objective::LeastMagnitude).unwrap();
+        assert_eq!(least, [Instr::Down(4), Instr::Forward(15)]);
+        assert_eq!(magnitude(&least).unwrap(), 19);
+
+        let fewest = plan(7, -10, Objective::FewestCommands).unwrap();
+        assert_eq!(
+            fewest,
+            [Instr::Forward(6), Instr::Up(10), Instr::Forward(1)]
+        );
+        // aims -1 for 4 units and -2 for 3 units
+        let least = plan(7, -10, Objective::LeastMagnitude).unwrap();
+        assert_eq!(
+            least,
+            [
+                Instr::Up(1),
+                Instr::Forward(4),
+                Instr::Up(1),
+                Instr::Forward(3)
+            ]
+        );
+        assert_eq!(magnitude(&least).unwrap(), 9);
+        let repeated = [Instr::Repeat(2, vec![Instr::Up(i64::MAX / 2 + 1)])];
+        assert!(magnitude(&repeated).is_err());
+        assert!(magnitude(&[Instr::Back(i64::MIN)]).is_err());
+
+        assert_eq!(plan(0, 0, Objective::LeastMagnitude).unwrap(), []);
+        assert_eq!(
+            plan(0, 3, Objective::FewestCommands)
+                .unwrap_err()
+                .to_string(),
+            "depth 3 cannot be reached without moving forward"
+        );
+        assert_eq!(
+            plan(-1, 0, Objective::FewestCommands)
+                .unwrap_err()
+                .to_string(),
+            "horizontal position -1 is behind the start"
+        );
+        assert_eq!(
+            plan(1, i64::MIN, Objective::FewestCommands)
+                .unwrap_err()
+                .to_string(),
+            format!("depth {} is out of range", i64::MIN)
+        );
+        assert_eq!(
+            plan(2, i64::MAX, Objective::LeastMagnitude)
+                .unwrap_err()
+                .to_string(),
+            format!("course to 2, {} is out of range", i64::MAX)
+        );
+        let fewest = plan(2, i64::MAX, Objective::FewestCommands).unwrap();
+        assert_eq!(
+            fewest,
+            [Instr::Forward(1), Instr::Down(i64::MAX), Instr::Forward(1)]
+        );
+    }
+
+    #[test]
+    fn plans_are_optimal() {
+        let size = 4;
+        let searched = searched_plans(size);
+        for x in 0..=size {
+            for depth in -size * size..=size * size {
+                let Some(&(commands, least)) = searched.get(&(x, depth)) else {
+                    assert!(plan(x, depth, Objective::FewestCommands).is_err());
+                    continue;
+                };
+                for objective in [Objective::FewestCommands, Objective::LeastMagnitude] {
+                    let course = plan(x, depth, objective).unwrap();
+                    let end = execute(&course, &Interpretation::Aim).unwrap();
+                    assert_eq!((end.x, end.depth), (x, depth), "{:?}", course);
+                    match objective {
+                        Objective::FewestCommands => assert_eq!(course.len(), commands),
+                        Objective::LeastMagnitude => assert_eq!(magnitude(&course).unwrap(), least),
+                    }
+                }
+            }
+        }
+    }
+
+    /// a current drifting the submarine one unit down for every unit it moves forward
+    struct Current;
+
+    impl Semantics for Current {
+        fn forward(&self, state: State, amount: i64) -> anyhow::Result<State> {
+            let state = Interpretation::Direct.forward(state, amount)?;
+            Interpretation::Direct.down(state, in_range(amount.checked_abs())?)
+        }
+
+        fn down(&self, state: State, amount: i64) -> anyhow::Result<State> {
+            Interpretation::Direct.down(state, amount)
+        }
+    }
+
+    #[test]
+    fn custom_semantics() {
+        assert_eq!(
+            execute(&EXAMPLE, &Current).unwrap(),
+            State {
+                x: 15,
+                depth: 25,
+                aim: 0
+            }
+        );
+        let course = [Instr::Back(3), Instr::Surface];
+        let states: Vec<(i64, i64)> = trajectory(&course, &Current)
+            .unwrap()
+            .iter()
+            .map(|s| (s.x, s.depth))
+            .collect();
+        assert_eq!(states, [(0, 0), (-3, 3), (-3, 0)]);
+    }
+
+    #[test]
+    fn overflows() {
+        let error = |course: &[Instr], semantics: Interpretation| {
+            execute(course, &semantics).unwrap_err().to_string()
+        };
+        let expected = "submarine leaves the range of 64 bit numbers";
+        let forward = [Instr::Forward(i64::MAX), Instr::Forward(1)];
+        assert_eq!(error(&forward, Interpretation::Direct), expected);
+        let dive = [Instr::Down(1 << 40), Instr::Forward(1 << 40)];
+        assert_eq!(error(&dive, Interpretation::Aim), expected);
+        assert_eq!(error(&[Instr::Up(i64::MIN)], Interpretation::Aim), expected);
+        assert!(execute(&dive, &Interpretation::Direct).is_ok());
+    }
+}
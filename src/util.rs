@@ -1,25 +1,31 @@
 use ndarray::Array2;
-use petgraph::graph::NodeIndex;
-use petgraph::Graph;
-
-use std::fmt::Debug;
-use std::str::FromStr;
 
 /// parses ascii based grids into Array2
-pub fn parse_array2<T>(input: &str) -> Array2<T>
+#[cfg(any(test, feature = "day9", feature = "day11", feature = "day15"))]
+pub fn parse_array2<T>(input: &str) -> anyhow::Result<Array2<T>>
 where
-    T: FromStr + Default + Debug,
-    <T as FromStr>::Err: Debug,
+    T: std::str::FromStr + Default,
+    <T as std::str::FromStr>::Err: std::error::Error + Send + Sync + 'static,
 {
-    let mut grid: Array2<T> =
-        Array2::default((input.lines().count(), input.lines().next().unwrap().len()));
+    let width = input
+        .lines()
+        .next()
+        .ok_or_else(|| anyhow!("empty grid"))?
+        .chars()
+        .count();
+    if width == 0 {
+        bail!("empty grid");
+    }
+    let mut grid: Array2<T> = Array2::default((input.lines().count(), width));
     for (y, line) in input.lines().enumerate() {
+        if line.chars().count() != width {
+            bail!("line {} is not {} cells wide", y + 1, width);
+        }
         for (x, digit) in line.chars().enumerate() {
-            let val = digit.to_string().parse().unwrap();
-            grid[(y, x)] = val;
+            grid[(y, x)] = digit.to_string().parse()?;
         }
     }
-    grid
+    Ok(grid)
 }
 
 /// shape of given array2 as tuple
@@ -28,29 +34,6 @@ pub fn shape2<T>(grid: &Array2<T>) -> (usize, usize) {
     (shape[0], shape[1])
 }
 
-/// converts given Array2 to a Graph
-pub fn array2_to_graph4<T: Clone>(grid: &Array2<T>) -> (Graph<T, T>, Array2<NodeIndex>) {
-    let mut graph: Graph<T, T> = Graph::new();
-    let (height, width) = shape2(grid);
-    let mut node_grid: Array2<NodeIndex> = Array2::default((height, width));
-    for (y, row) in grid.rows().into_iter().enumerate() {
-        for (x, col) in row.iter().enumerate() {
-            node_grid[(y, x)] = graph.add_node(col.clone());
-        }
-    }
-    for (y, row) in grid.rows().into_iter().enumerate() {
-        for (x, col) in row.iter().enumerate() {
-            for (ny, nx) in get_neighbors4(grid, &(y, x)) {
-                let from_node = node_grid[(y, x)];
-                let to_node = node_grid[(ny, nx)];
-                graph.add_edge(from_node, to_node, grid[(ny, nx)].clone());
-                graph.add_edge(to_node, from_node, col.clone());
-            }
-        }
-    }
-    (graph, node_grid)
-}
-
 /// gets 4 neighbor positions of given pos in grid
 pub fn get_neighbors4<T>(grid: &Array2<T>, pos: &(usize, usize)) -> Vec<(usize, usize)> {
     let (height, width) = shape2(grid);
@@ -75,7 +58,8 @@ pub fn get_neighbors4<T>(grid: &Array2<T>, pos: &(usize, usize)) -> Vec<(usize,
 }
 
 /// gets 8 neighbor positions of given pos in grid
-pub fn get_neighbors8(grid: &Array2<u8>, pos: &(usize, usize)) -> Vec<(usize, usize)> {
+#[cfg(any(all(test, feature = "day15"), feature = "day11", feature = "day12"))]
+pub fn get_neighbors8<T>(grid: &Array2<T>, pos: &(usize, usize)) -> Vec<(usize, usize)> {
     let (height, width) = shape2(grid);
     let mut list = get_neighbors4(grid, pos);
     // up/left
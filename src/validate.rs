@@ -0,0 +1,118 @@
+use std::fmt::{Display, Formatter};
+
+/// A puzzle input invariant which does not hold, with its location in the input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /// 1-based line number
+    pub line: Option<usize>,
+    /// 1-based column number
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+impl Violation {
+    /// violation concerning the input as a whole
+    pub fn general<S: Into<String>>(message: S) -> Violation {
+        Violation {
+            line: None,
+            column: None,
+            message: message.into(),
+        }
+    }
+
+    /// violation located at given 1-based line
+    pub fn at_line<S: Into<String>>(line: usize, message: S) -> Violation {
+        Violation {
+            line: Some(line),
+            column: None,
+            message: message.into(),
+        }
+    }
+
+    /// violation located at given 1-based line and column
+    pub fn at<S: Into<String>>(line: usize, column: usize, message: S) -> Violation {
+        Violation {
+            line: Some(line),
+            column: Some(column),
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match (self.line, self.column) {
+            (Some(line), Some(column)) => {
+                write!(f, "line {}, column {}: {}", line, column, self.message)
+            }
+            (Some(line), None) => write!(f, "line {}: {}", line, self.message),
+            _ => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// reports a failing generator as a single violation
+pub fn parse_error<T>(result: anyhow::Result<T>) -> Vec<Violation> {
+    match result {
+        Ok(_) => Vec::new(),
+        Err(e) => vec![Violation::general(format!("{:#}", e))],
+    }
+}
+
+/// checks that the input is a non-empty rectangular grid of single digits,
+/// as expected by `util::parse_array2`
+#[allow(dead_code)] // unused when no grid day is compiled
+pub(crate) fn digit_grid(input: &str) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    let width = match input.lines().next() {
+        Some(first) if !first.is_empty() => first.chars().count(),
+        _ => return vec![Violation::general("grid is empty")],
+    };
+    for (y, line) in input.lines().enumerate() {
+        let len = line.chars().count();
+        if len != width {
+            violations.push(Violation::at_line(
+                y + 1,
+                format!("row is {} cells wide, expected {}", len, width),
+            ));
+        }
+        for (x, c) in line.chars().enumerate() {
+            if !c.is_ascii_digit() {
+                violations.push(Violation::at(
+                    y + 1,
+                    x + 1,
+                    format!("`{}` is not a digit", c),
+                ));
+            }
+        }
+    }
+    violations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn digit_grid_reports_every_violation() {
+        assert_eq!(digit_grid("123\n456"), vec![]);
+        assert_eq!(digit_grid(""), vec![Violation::general("grid is empty")]);
+        assert_eq!(
+            digit_grid("123\n4x\n789"),
+            vec![
+                Violation::at_line(2, "row is 2 cells wide, expected 3"),
+                Violation::at(2, 2, "`x` is not a digit"),
+            ]
+        );
+    }
+
+    #[test]
+    fn display_location() {
+        assert_eq!(
+            Violation::at(2, 3, "bad").to_string(),
+            "line 2, column 3: bad"
+        );
+        assert_eq!(Violation::at_line(2, "bad").to_string(), "line 2: bad");
+        assert_eq!(Violation::general("bad").to_string(), "bad");
+    }
+}
@@ -0,0 +1,23 @@
+//! Anomaly mode of day 1 on a sweep with missing readings, as the runner's `anomalies` uses it
+
+#![cfg(feature = "day1")]
+
+use adventofcode_rust_2021::runner::anomalies;
+use adventofcode_rust_2021::sonar::Reason;
+
+#[test]
+fn gapped_sweep() {
+    let found = anomalies("199\n200\n\n\n\n208\n210\n\n200\n20\n207").unwrap();
+    let summary: Vec<(usize, Option<i64>, Reason)> =
+        found.iter().map(|a| (a.index, a.depth, a.reason)).collect();
+    assert_eq!(
+        summary,
+        [
+            (2, None, Reason::Gap { missing: 3 }),
+            (7, None, Reason::Gap { missing: 1 }),
+            (9, Some(20), Reason::Outlier { median: 200 }),
+            (9, Some(20), Reason::Drop { previous: 200 }),
+        ]
+    );
+    assert!(anomalies("199\nx").is_err());
+}
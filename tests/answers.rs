@@ -0,0 +1,54 @@
+//! Regression suite: every registered solution run against the real puzzle input
+
+use adventofcode_rust_2021::answer::Answer;
+use adventofcode_rust_2021::runner::{load_input, solutions};
+
+fn expected(day: u8, part: u8) -> Answer {
+    match (day, part) {
+        (1, 1) => Answer::Int(1292),
+        (1, 2) => Answer::Int(1262),
+        (2, 1) => Answer::Int(1882980),
+        (2, 2) => Answer::Int(1971232560),
+        (3, 1) => Answer::Int(3148794),
+        (3, 2) => Answer::Int(2795310),
+        (4, 1) => Answer::Int(41503),
+        (4, 2) => Answer::Int(3178),
+        (5, 1) => Answer::Int(7297),
+        (5, 2) => Answer::Int(21038),
+        (6, 1) => Answer::Int(352195),
+        (6, 2) => Answer::Int(1600306001288),
+        (7, 1) => Answer::Int(349812),
+        (7, 2) => Answer::Int(99763899),
+        (8, 1) => Answer::Int(495),
+        (8, 2) => Answer::Int(1055164),
+        (9, 1) => Answer::Int(478),
+        (9, 2) => Answer::Int(1327014),
+        (10, 1) => Answer::Int(392367),
+        (10, 2) => Answer::Int(2192104158),
+        (11, 1) => Answer::Int(1755),
+        (11, 2) => Answer::Int(212),
+        (12, 1) => Answer::Int(3463),
+        (13, 1) => Answer::Int(847),
+        (13, 2) => Answer::Text("BCZRCEAB".into()),
+        (14, 1) => Answer::Int(2233),
+        (14, 2) => Answer::Int(2884513602164),
+        (15, 1) => Answer::Int(363),
+        (15, 2) => Answer::Int(2835),
+        _ => panic!("no expected answer for day {} part {}", day, part),
+    }
+}
+
+#[test]
+fn all_answers() {
+    for solution in solutions() {
+        let input = load_input(solution.day).expect("failed to read input");
+        let run = solution.run(&input).expect("failed to parse input");
+        assert_eq!(
+            run.answer,
+            expected(solution.day, solution.part),
+            "day {} part {}",
+            solution.day,
+            solution.part
+        );
+    }
+}
@@ -0,0 +1,58 @@
+//! Malformed inputs must be rejected with an error instead of a panic, and inputs which are
+//! accepted must not make the solvers panic either.
+//! The fuzz targets in `fuzz/` explore this further with arbitrary bytes.
+
+use adventofcode_rust_2021::runner::{exercise, generators};
+use std::time::Duration;
+
+const MALFORMED: &[&str] = &[
+    "",
+    "\n",
+    "garbage",
+    "-",
+    ",",
+    "1,",
+    " -> ",
+    "a | ",
+    "forward",
+    "99999999999999999999999999",
+    "12\n345",
+    "9,9 -> 9",
+    "fold along x=99999999999999999999999",
+    "18446744073709551615,0",
+    "NNCB\n\nCH -> ",
+    "1\n\n1 2 3 4 5 6",
+    "ÄÖÜ\nab",
+    "3,9,1",
+    "0,0 -> 2,1",
+    "1,1\n\nfold along y=4096",
+    "9",
+    "()",
+    "a b c d e f g ab abc abcd | ab",
+    "1,1\n\nfold along y=5",
+    "1,1\n3,3\n\nfold along x=1\nfold along x=5",
+    "100000,0",
+    "start-A\nA-end\nA-B",
+    "down 99999999999\nforward 99999999999",
+    "forward 9223372036854775807\nforward 1",
+    "9223372036854775807\n-9223372036854775808",
+    "1,4294967295\n\n4294967295 1\n4294967294 4294967293",
+];
+
+#[test]
+fn generators_never_panic() {
+    for generator in generators() {
+        for input in MALFORMED {
+            let _ = generator.parse(input);
+        }
+    }
+}
+
+#[test]
+fn solvers_never_panic() {
+    for generator in generators() {
+        for input in MALFORMED {
+            let _ = exercise(generator.day, input, Duration::from_millis(100));
+        }
+    }
+}